@@ -5,6 +5,7 @@ use std::io::{self, BufRead, BufReader};
 use std::num::ParseIntError;
 use std::path::PathBuf;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Invalid<'a> {
     Arg(&'a str),
@@ -13,8 +14,11 @@ enum Invalid<'a> {
     Color(&'a str),
     Parse(ParseIntError),
     Id(&'a str),
+    // 'smallest <k>' asked for more games than the input has (this many)
+    TooFew(usize),
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
@@ -22,10 +26,18 @@ enum Error<'a> {
     Inv(Invalid<'a>),
 }
 
+const USAGE: &str = "Use '1', '2', 'possible <red> <green> <blue>', 'minimal', 'stats' or 'smallest <k>' (add '--json' for JSON)";
+
 fn main() -> Result<(), Error<'static>> {
     let root_dir = env::current_dir().expect("Failed to get current working directory");
     let args: Vec<String> = env::args().collect();
- 
+    let json = args.iter().any(|arg| arg == "--json");
+    let args: Vec<&str> = args
+        .iter()
+        .map(|arg| arg.as_str())
+        .filter(|&arg| arg != "--json")
+        .collect();
+
     // to test with sample data
     // let file_path = root_dir.join("src").join("sample-1.txt");
     let file_path = root_dir.join("src").join("input.txt");
@@ -33,22 +45,10 @@ fn main() -> Result<(), Error<'static>> {
     // Create a Line instance from a file
     let mut line_reader = Line::new(file_path).map_err(Error::Io)?;
 
-    let mut total: u32 = 0;
-    let func = match args.get(1) {
-        Some(arg) => match arg.as_str() {
-            "1" => part_one,
-            "2" => part_two,
-            _ => {
-                return Err(Error::Inv(Invalid::Arg("Invalid argument. Use '1' or '2'")));
-            }
-        },
-        None => {
-            return Err(Error::NoArg("Missing argument. Use '1' or '2'"));
-        }
-    };
+    let mut games: Vec<Game> = Vec::new();
     loop {
         match line_reader.read() {
-            Ok(Some(line)) => total += func(line).map_err(Error::Inv)?,
+            Ok(Some(line)) => games.push(parse_game(&line).map_err(Error::Inv)?),
             Ok(None) => {
                 // End of file reached
                 break;
@@ -60,18 +60,52 @@ fn main() -> Result<(), Error<'static>> {
         }
     }
 
-    println!("Total: {}", total);
+    let report = match args.get(1) {
+        Some(&"1") => {
+            println!("Total: {}", part_one(&games));
+            return Ok(());
+        }
+        Some(&"2") => {
+            println!("Total: {}", part_two(&games));
+            return Ok(());
+        }
+        Some(&"possible") => {
+            let bag = Bag {
+                red: parse_arg(&args, 2)?,
+                green: parse_arg(&args, 3)?,
+                blue: parse_arg(&args, 4)?,
+            };
+            possible(&games, &bag)
+        }
+        Some(&"minimal") => minimal(&games),
+        Some(&"stats") => stats(&games),
+        Some(&"smallest") => smallest(&games, parse_arg(&args, 2)? as usize)?,
+        Some(_) => return Err(Error::Inv(Invalid::Arg(USAGE))),
+        None => return Err(Error::NoArg(USAGE)),
+    };
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report.to_table());
+    }
 
     Ok(())
 }
 
+fn parse_arg(args: &[&str], index: usize) -> Result<u32, Error<'static>> {
+    args.get(index)
+        .ok_or(Error::NoArg(USAGE))?
+        .parse::<u32>()
+        .map_err(|e| Error::Inv(Invalid::Parse(e)))
+}
+
 struct Line {
     reader: BufReader<File>,
 }
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -98,19 +132,12 @@ enum Color {
     Blue,
 }
 
-const RED: u16 = 12;
-const GREEN: u16 = 13;
-const BLUE: u16 = 14;
-
-impl Color {
-    fn is_invalid(&self, num: u16) -> bool {
-        match self {
-            Color::Red => num > RED,
-            Color::Green => num > GREEN,
-            Color::Blue => num > BLUE,
-        }
-    }
-}
+// the bag the elf asks about in part one
+const ELF_BAG: Bag = Bag {
+    red: 12,
+    green: 13,
+    blue: 14,
+};
 
 fn create_map() -> HashMap<&'static str, Color> {
     let color_map: HashMap<&str, Color> = [
@@ -125,53 +152,7 @@ fn create_map() -> HashMap<&'static str, Color> {
     color_map
 }
 
-#[allow(dead_code)]
-fn part_one<'a>(line: String) -> Result<u32, Invalid<'a>> {
-    let splitted: Vec<&str> = line.split(":").collect();
-    let game_id = splitted
-        .get(0)
-        .map(|&s| {
-            s.split_ascii_whitespace()
-                .collect::<Vec<&str>>()
-                .get(1)
-                .map(|&id| id.parse::<u16>().map_err(Invalid::Parse))
-                .ok_or(Invalid::Id("No Id"))
-        })
-        .ok_or(Invalid::Id("No Game <Id>"))???;
-
-    let game = *splitted
-        .get(1)
-        .ok_or(Invalid::Line("The line does not contain ':'"))?;
-    let color_map = create_map();
-    let sets: Vec<&str> = game.split(";").collect();
-    let mut valid = true;
-
-    'outer: for set in sets {
-        let cubes: Vec<&str> = set.split(",").collect();
-        for cube in cubes {
-            let cube_info: Vec<&str> = cube.split_ascii_whitespace().collect();
-            let num = cube_info
-                .get(0)
-                .map(|&n| n.parse::<u16>().map_err(Invalid::Parse))
-                .ok_or(Invalid::Cube("No number found"))??;
-            let color = cube_info
-                .get(1)
-                .map(|&c| color_map.get(c).ok_or(Invalid::Color("Invalid color")))
-                .ok_or(Invalid::Cube("No color found"))??;
-            valid = !color.is_invalid(num);
-            if !valid {
-                break 'outer;
-            }
-        }
-    }
-    if valid {
-        Ok(game_id as u32)
-    } else {
-        Ok(0)
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct Bag {
     red: u32,
     green: u32,
@@ -205,27 +186,57 @@ impl Bag {
             }
         }
     }
-    fn power(&self) -> u32 {
-        self.red * self.green * self.blue
+    fn get(&self, color: &Color) -> u32 {
+        match color {
+            Color::Red => self.red,
+            Color::Green => self.green,
+            Color::Blue => self.blue,
+        }
+    }
+    // in u64, three counts of a few thousand already overflow a u32
+    fn power(&self) -> u64 {
+        self.red as u64 * self.green as u64 * self.blue as u64
+    }
+    fn total(&self) -> u32 {
+        self.red + self.green + self.blue
+    }
+    // can every cube of `other` be drawn from this bag?
+    fn contains(&self, other: &Bag) -> bool {
+        other.red <= self.red && other.green <= self.green && other.blue <= self.blue
     }
 }
 
-#[allow(dead_code)]
-fn part_two<'a>(line: String) -> Result<u32, Invalid<'a>> {
-    let splitted: Vec<&str> = line.split(":").collect();
+// a game is reduced to its minimal bag: the most cubes of each color ever shown
+struct Game {
+    id: u32,
+    bag: Bag,
+}
+
+fn parse_game(line: &str) -> Result<Game, Invalid<'static>> {
+    let splitted: Vec<&str> = line.split(':').collect();
+    let id = splitted
+        .first()
+        .map(|&s| {
+            s.split_ascii_whitespace()
+                .collect::<Vec<&str>>()
+                .get(1)
+                .map(|&id| id.parse::<u32>().map_err(Invalid::Parse))
+                .ok_or(Invalid::Id("No Id"))
+        })
+        .ok_or(Invalid::Id("No Game <Id>"))???;
 
     let game = *splitted
         .get(1)
         .ok_or(Invalid::Line("The line does not contain ':'"))?;
     let color_map = create_map();
-    let sets: Vec<&str> = game.split(";").collect();
+    let sets: Vec<&str> = game.split(';').collect();
     let mut bag = Bag::new();
     for set in sets {
-        let cubes: Vec<&str> = set.split(",").collect();
+        let cubes: Vec<&str> = set.split(',').collect();
         for cube in cubes {
             let cube_info: Vec<&str> = cube.split_ascii_whitespace().collect();
             let num = cube_info
-                .get(0)
+                .first()
                 .map(|&n| n.parse::<u16>().map_err(Invalid::Parse))
                 .ok_or(Invalid::Cube("No number found"))??;
             let color = cube_info
@@ -235,6 +246,226 @@ fn part_two<'a>(line: String) -> Result<u32, Invalid<'a>> {
             bag.update(color, num as u32);
         }
     }
-    // println!("{:?} = {}", bag, bag.power());
-    Ok(bag.power())
+    Ok(Game { id, bag })
+}
+
+fn part_one(games: &[Game]) -> u32 {
+    games
+        .iter()
+        .filter(|game| ELF_BAG.contains(&game.bag))
+        .map(|game| game.id)
+        .sum()
+}
+
+fn part_two(games: &[Game]) -> u64 {
+    games.iter().map(|game| game.bag.power()).sum()
+}
+
+// #################################################################################
+// queries
+
+enum Cell {
+    Int(u64),
+    Float(f64),
+    Bool(bool),
+    Text(&'static str),
+}
+
+impl Cell {
+    fn render(&self) -> String {
+        match self {
+            Cell::Int(n) => n.to_string(),
+            Cell::Float(x) => format!("{:.2}", x),
+            Cell::Bool(b) => b.to_string(),
+            Cell::Text(s) => s.to_string(),
+        }
+    }
+    fn to_json(&self) -> String {
+        match self {
+            Cell::Text(s) => format!("\"{}\"", s),
+            _ => self.render(),
+        }
+    }
+}
+
+struct Report {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Report {
+    fn to_table(&self) -> String {
+        let rendered: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Cell::render).collect())
+            .collect();
+        let widths: Vec<usize> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                rendered
+                    .iter()
+                    .map(|row| row[i].len())
+                    .fold(header.len(), usize::max)
+            })
+            .collect();
+        let mut out = String::new();
+        let headers: Vec<String> = self.headers.iter().map(|h| h.to_string()).collect();
+        for row in std::iter::once(&headers).chain(rendered.iter()) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, &width)| format!("{:>width$}", cell, width = width))
+                .collect();
+            out.push_str(&cells.join("  "));
+            out.push('\n');
+        }
+        out
+    }
+    fn to_json(&self) -> String {
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let fields: Vec<String> = self
+                    .headers
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(header, cell)| format!("\"{}\":{}", header, cell.to_json()))
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            })
+            .collect();
+        format!("[{}]", rows.join(","))
+    }
+}
+
+// which games could have been played with the given bag
+fn possible(games: &[Game], bag: &Bag) -> Report {
+    Report {
+        headers: vec!["game", "possible"],
+        rows: games
+            .iter()
+            .map(|game| {
+                vec![
+                    Cell::Int(game.id.into()),
+                    Cell::Bool(bag.contains(&game.bag)),
+                ]
+            })
+            .collect(),
+    }
+}
+
+// the fewest cubes of each color that make each game possible
+fn minimal(games: &[Game]) -> Report {
+    Report {
+        headers: vec!["game", "red", "green", "blue", "power"],
+        rows: games
+            .iter()
+            .map(|game| {
+                vec![
+                    Cell::Int(game.id.into()),
+                    Cell::Int(game.bag.red.into()),
+                    Cell::Int(game.bag.green.into()),
+                    Cell::Int(game.bag.blue.into()),
+                    Cell::Int(game.bag.power()),
+                ]
+            })
+            .collect(),
+    }
+}
+
+// per color maximum and average of the minimal bags over all games
+fn stats(games: &[Game]) -> Report {
+    let colors = [
+        ("red", Color::Red),
+        ("green", Color::Green),
+        ("blue", Color::Blue),
+    ];
+    Report {
+        headers: vec!["color", "max", "average"],
+        rows: colors
+            .iter()
+            .map(|(name, color)| {
+                let max = games
+                    .iter()
+                    .map(|game| game.bag.get(color))
+                    .max()
+                    .unwrap_or(0);
+                let sum: u64 = games.iter().map(|game| game.bag.get(color) as u64).sum();
+                let average = if games.is_empty() {
+                    0.0
+                } else {
+                    sum as f64 / games.len() as f64
+                };
+                vec![
+                    Cell::Text(name),
+                    Cell::Int(max.into()),
+                    Cell::Float(average),
+                ]
+            })
+            .collect(),
+    }
+}
+
+// the bag with the fewest cubes (ties broken by power) that makes at least
+// `k` games possible. Every color of the best bag equals some game's minimum,
+// so it is enough to try each red and green candidate and pick the k-th
+// smallest blue among the games that fit.
+fn find_smallest(games: &[Game], k: usize) -> Option<Bag> {
+    if k == 0 {
+        return Some(Bag::new());
+    }
+    let mut reds: Vec<u32> = games.iter().map(|game| game.bag.red).collect();
+    let mut greens: Vec<u32> = games.iter().map(|game| game.bag.green).collect();
+    reds.sort();
+    reds.dedup();
+    greens.sort();
+    greens.dedup();
+    let mut best: Option<Bag> = None;
+    for &red in reds.iter() {
+        for &green in greens.iter() {
+            let mut blues: Vec<u32> = games
+                .iter()
+                .filter(|game| game.bag.red <= red && game.bag.green <= green)
+                .map(|game| game.bag.blue)
+                .collect();
+            if blues.len() < k {
+                continue;
+            }
+            blues.sort();
+            let bag = Bag {
+                red,
+                green,
+                blue: blues[k - 1],
+            };
+            let better = match &best {
+                None => true,
+                Some(b) => (bag.total(), bag.power()) < (b.total(), b.power()),
+            };
+            if better {
+                best = Some(bag);
+            }
+        }
+    }
+    best
+}
+
+// there is always a bag unless k is larger than the number of games
+fn smallest(games: &[Game], k: usize) -> Result<Report, Error<'static>> {
+    let bag = find_smallest(games, k).ok_or(Error::Inv(Invalid::TooFew(games.len())))?;
+    let count = games.iter().filter(|game| bag.contains(&game.bag)).count();
+    Ok(Report {
+        headers: vec!["k", "red", "green", "blue", "cubes", "possible"],
+        rows: vec![vec![
+            Cell::Int(k as u64),
+            Cell::Int(bag.red.into()),
+            Cell::Int(bag.green.into()),
+            Cell::Int(bag.blue.into()),
+            Cell::Int(bag.total().into()),
+            Cell::Int(count as u64),
+        ]],
+    })
 }
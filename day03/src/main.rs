@@ -3,11 +3,18 @@ use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::path::PathBuf;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Invalid<'a> {
     Arg(&'a str),
+    Rule(&'a str),
+    // the number starting at (row, column) does not fit in a u64
+    Number(usize, usize),
+    // a total does not fit in a u64
+    Overflow(&'a str),
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
@@ -15,6 +22,9 @@ enum Error<'a> {
     Inv(Invalid<'a>),
 }
 
+const USAGE: &str =
+    "Use '1', '2', 'dot', 'json' or 'rules <rule>...', e.g. 'rules *=2:product #>=3:sum'";
+
 fn main() -> Result<(), Error<'static>> {
     let root_dir = env::current_dir().expect("Failed to get current working directory");
     let args: Vec<String> = env::args().collect();
@@ -26,34 +36,35 @@ fn main() -> Result<(), Error<'static>> {
     // Create a Line instance from a file
     let mut line_reader = Line::new(file_path).map_err(Error::Io)?;
 
-    let mut total: u32 = 0;
-
-    let func = match args.get(1) {
+    let command = match args.get(1) {
         Some(arg) => match arg.as_str() {
-            "1" => part_one,
-            "2" => part_two,
+            "1" | "2" | "rules" | "dot" | "json" => arg.as_str(),
+            _ => return Err(Error::Inv(Invalid::Arg(USAGE))),
+        },
+        None => return Err(Error::NoArg(USAGE)),
+    };
+    // the rest is the adjacency ('4' or '8' neighbours, default is 8) and,
+    // for 'rules', the gear rules
+    let mut adjacency = Adjacency::Eight;
+    let mut rules = Vec::new();
+    for arg in args.iter().skip(2) {
+        match arg.as_str() {
+            "4" => adjacency = Adjacency::Four,
+            "8" => adjacency = Adjacency::Eight,
+            rule if command == "rules" => rules.push(Rule::parse(rule).map_err(Error::Inv)?),
             _ => {
-                return Err(Error::Inv(Invalid::Arg("Invalid argument. Use '1' or '2'")));
+                return Err(Error::Inv(Invalid::Arg(
+                    "Invalid adjacency. Use '4' or '8'",
+                )))
             }
-        },
-        None => {
-            return Err(Error::NoArg("Missing argument. Use '1' or '2'"));
         }
-    };
-    let mut lines = Lines::new();
+    }
+    let mut analyser = Analyser::new(adjacency);
     loop {
         match line_reader.read() {
-            Ok(Some(line)) => {
-                lines.update(Some(line));
-                let subtotal = func(&lines);
-                total += subtotal;
-            }
+            Ok(Some(line)) => analyser.feed(&line).map_err(Error::Inv)?,
             Ok(None) => {
                 // End of file reached
-                // one more time...
-                lines.update(None);
-                let subtotal = func(&lines);
-                total += subtotal;
                 break;
             }
             Err(e) => {
@@ -63,6 +74,21 @@ fn main() -> Result<(), Error<'static>> {
         }
     }
 
+    let schematic = analyser.finish();
+    let total = (match command {
+        "1" => part_one(&schematic),
+        "2" => part_two(&schematic),
+        "rules" => apply_rules(&schematic, &rules),
+        "dot" => {
+            print!("{}", schematic.to_dot());
+            return Ok(());
+        }
+        _ => {
+            println!("{}", schematic.to_json());
+            return Ok(());
+        }
+    })
+    .map_err(Error::Inv)?;
     println!("Total: {}", total);
 
     Ok(())
@@ -77,7 +103,7 @@ struct Line {
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -95,327 +121,384 @@ impl Line {
 // #################################################################################
 // #################################################################################
 // #################################################################################
-// enum
-enum Char {
-    Digit(u8),
-    Important,
-    Other,
-}
+// schematic
 
-// struct
-struct LinesIterator {
-    lines: Lines,
-    index: usize,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Adjacency {
+    // only the cells sharing an edge
+    Four,
+    // diagonals included
+    Eight,
 }
 
-impl Iterator for LinesIterator {
-    type Item = (usize, Option<char>, Option<char>, Option<char>);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let index = self.index;
-        let ch_above = self.lines.above.as_ref().and_then(|s| s.chars().nth(index));
-        let ch_middle = self
-            .lines
-            .middle
-            .as_ref()
-            .and_then(|s| s.chars().nth(index));
-        let ch_below = self.lines.below.as_ref().and_then(|s| s.chars().nth(index));
-
-        if ch_above.is_some() || ch_middle.is_some() || ch_below.is_some() {
-            self.index += 1;
-            Some((index, ch_above, ch_middle, ch_below))
-        } else {
-            None
-        }
-    }
+#[derive(Debug, Clone)]
+struct Number {
+    value: u64,
+    row: usize,
+    // columns [start, end)
+    start: usize,
+    end: usize,
 }
 
-#[derive(Clone)]
-struct Lines {
-    above: Option<String>,
-    middle: Option<String>,
-    below: Option<String>,
+#[derive(Debug, Clone)]
+struct Symbol {
+    ch: char,
+    row: usize,
+    col: usize,
 }
 
-impl Lines {
-    fn new() -> Self {
-        Lines {
-            above: None,
-            middle: None,
-            below: None,
-        }
-    }
-    fn iter(&self) -> LinesIterator {
-        LinesIterator {
-            lines: self.clone(),
-            index: 0,
+impl Adjacency {
+    fn touches(&self, num: &Number, sym: &Symbol) -> bool {
+        let row_distance = num.row.abs_diff(sym.row);
+        match self {
+            Adjacency::Eight => row_distance <= 1 && sym.col + 1 >= num.start && sym.col <= num.end,
+            Adjacency::Four => match row_distance {
+                0 => sym.col + 1 == num.start || sym.col == num.end,
+                1 => sym.col >= num.start && sym.col < num.end,
+                _ => false,
+            },
         }
     }
-    fn update(&mut self, line: Option<String>) {
-        self.above = self.middle.take();
-        self.middle = self.below.take();
-        self.below = line;
-    }
 }
 
-#[derive(Debug, Clone)]
-struct Number {
-    value: u32,
-    start: usize,
-    len: usize,
+// the parsed schematic: every number, every symbol, and which of them touch,
+// kept both ways as sorted index lists
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    // symbols_of[number] and numbers_of[symbol]
+    symbols_of: Vec<Vec<usize>>,
+    numbers_of: Vec<Vec<usize>>,
 }
 
-impl Number {
-    fn new<'a>(digit: u8, start: usize) -> Self {
-        Number {
-            value: digit as u32,
-            start,
-            len: 1,
-        }
-    }
-    fn add(mut self, digit: u8) -> Self {
-        self.value = self.value * 10 + digit as u32;
-        self.len += 1;
-        self
-    }
-    fn end(&self) -> usize {
-        self.len + self.start
+impl Schematic {
+    fn numbers_of(&self, symbol: usize) -> Vec<&Number> {
+        self.numbers_of[symbol]
+            .iter()
+            .map(|&num| &self.numbers[num])
+            .collect()
+    }
+    fn is_part(&self, number: usize) -> bool {
+        !self.symbols_of[number].is_empty()
+    }
+    // the (number, symbol) pairs that touch, by number then symbol
+    fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.symbols_of
+            .iter()
+            .enumerate()
+            .flat_map(|(num, symbols)| symbols.iter().map(move |&sym| (num, sym)))
     }
 }
 
-struct Container<T, const SIZE: usize> {
-    temps: [Option<T>; SIZE],
-    contents: Vec<T>,
+// reads the schematic one line at a time. Only the previous row is kept around
+// for linking, so the lines can be as wide as they like.
+struct Analyser {
+    adjacency: Adjacency,
+    row: usize,
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    symbols_of: Vec<Vec<usize>>,
+    numbers_of: Vec<Vec<usize>>,
+    // indices of the numbers and symbols found on the previous row
+    above_numbers: Vec<usize>,
+    above_symbols: Vec<usize>,
 }
 
-impl Container<Number, 1> {
-    fn new() -> Self {
-        Container {
-            temps: [None],
-            contents: Vec::new(),
+impl Analyser {
+    fn new(adjacency: Adjacency) -> Self {
+        Analyser {
+            adjacency,
+            row: 0,
+            numbers: Vec::new(),
+            symbols: Vec::new(),
+            symbols_of: Vec::new(),
+            numbers_of: Vec::new(),
+            above_numbers: Vec::new(),
+            above_symbols: Vec::new(),
         }
     }
-    fn update(&mut self, start: usize, digit: u8) {
-        if self.temps[0].is_none() {
-            self.temps[0] = Some(Number::new(digit, start));
-        } else if let Some(num) = self.temps[0].take() {
-            self.temps[0] = Some(num.add(digit));
+    fn feed(&mut self, line: &str) -> Result<(), Invalid<'static>> {
+        let line = line.trim_end_matches(['\n', '\r']);
+        let mut row_numbers: Vec<usize> = Vec::new();
+        let mut row_symbols: Vec<usize> = Vec::new();
+        let mut current: Option<Number> = None;
+        for (col, ch) in line.chars().enumerate() {
+            if let Some(digit) = ch.to_digit(10) {
+                let num = current.get_or_insert(Number {
+                    value: 0,
+                    row: self.row,
+                    start: col,
+                    end: col,
+                });
+                num.value = num
+                    .value
+                    .checked_mul(10)
+                    .and_then(|value| value.checked_add(digit as u64))
+                    .ok_or(Invalid::Number(num.row, num.start))?;
+                num.end = col + 1;
+                continue;
+            }
+            if let Some(num) = current.take() {
+                row_numbers.push(self.add_number(num));
+            }
+            if ch != '.' && !ch.is_whitespace() {
+                row_symbols.push(self.symbols.len());
+                self.symbols.push(Symbol {
+                    ch,
+                    row: self.row,
+                    col,
+                });
+                self.numbers_of.push(Vec::new());
+            }
         }
-    }
-    fn clear(&mut self) {
-        if let Some(it) = self.temps[0].take() {
-            self.contents.push(it);
+        if let Some(num) = current.take() {
+            row_numbers.push(self.add_number(num));
         }
-    }
-}
-
-impl Container<Number, 3> {
-    fn new() -> Self {
-        Container {
-            temps: [None, None, None],
-            contents: Vec::new(),
+        let above_numbers = std::mem::take(&mut self.above_numbers);
+        let above_symbols = std::mem::take(&mut self.above_symbols);
+        for &num in row_numbers.iter() {
+            self.link_row(num, &above_symbols);
+            self.link_row(num, &row_symbols);
         }
-    }
-    fn update(&mut self, index: usize, start: usize, digit: u8) {
-        self.is_out_of_bound(index);
-        if self.temps[index].is_none() {
-            self.temps[index] = Some(Number::new(digit, start));
-        } else if let Some(num) = self.temps[index].take() {
-            self.temps[index] = Some(num.add(digit));
+        for &num in above_numbers.iter() {
+            self.link_row(num, &row_symbols);
         }
-    }
-    fn clear(&mut self, index: usize) {
-        self.is_out_of_bound(index);
-        if let Some(it) = self.temps[index].take() {
-            self.contents.push(it);
+        self.above_numbers = row_numbers;
+        self.above_symbols = row_symbols;
+        self.row += 1;
+        Ok(())
+    }
+    fn add_number(&mut self, num: Number) -> usize {
+        self.numbers.push(num);
+        self.symbols_of.push(Vec::new());
+        self.numbers.len() - 1
+    }
+    // `symbols` is one row sorted by column, and only the columns from
+    // start - 1 to end can touch the number, so a binary search finds them
+    // without looking at the rest of the row
+    fn link_row(&mut self, num: usize, symbols: &[usize]) {
+        let number = &self.numbers[num];
+        let (low, high) = (number.start.saturating_sub(1), number.end);
+        let first = symbols.partition_point(|&sym| self.symbols[sym].col < low);
+        for &sym in symbols[first..].iter() {
+            if self.symbols[sym].col > high {
+                break;
+            }
+            if self
+                .adjacency
+                .touches(&self.numbers[num], &self.symbols[sym])
+            {
+                self.symbols_of[num].push(sym);
+                self.numbers_of[sym].push(num);
+            }
         }
     }
-    fn is_out_of_bound(&self, index: usize) {
-        if index > 2 {
-            panic!("Symbol index must be between 0, 1, or 2");
+    fn finish(mut self) -> Schematic {
+        for list in self.symbols_of.iter_mut().chain(self.numbers_of.iter_mut()) {
+            list.sort();
+        }
+        Schematic {
+            numbers: self.numbers,
+            symbols: self.symbols,
+            symbols_of: self.symbols_of,
+            numbers_of: self.numbers_of,
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct Symbol {
-    start: usize,
-    len: usize,
+// ##########################################################################
+// main
+// part one
+fn part_one(schematic: &Schematic) -> Result<u64, Invalid<'static>> {
+    schematic
+        .numbers
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| schematic.is_part(*i))
+        .try_fold(0u64, |total, (_, num)| total.checked_add(num.value))
+        .ok_or(Invalid::Overflow("The sum of the part numbers"))
 }
 
-impl Symbol {
-    fn new(start: usize) -> Self {
-        Symbol { len: 1, start }
-    }
-    fn add(mut self) -> Self {
-        self.len += 1;
-        self
-    }
-    fn end(&self) -> usize {
-        self.len + self.start
-    }
+// part two
+fn part_two(schematic: &Schematic) -> Result<u64, Invalid<'static>> {
+    let gear = Rule {
+        symbol: '*',
+        count: Count::Exactly(2),
+        combine: Combine::Product,
+    };
+    apply_rules(schematic, &[gear])
 }
 
-impl Container<Symbol, 3> {
-    fn new() -> Self {
-        Container {
-            temps: [None, None, None],
-            contents: Vec::new(),
-        }
-    }
-    fn update(&mut self, index: usize, start: usize) {
-        self.is_out_of_bound(index);
-        if self.temps[index].is_none() {
-            self.temps[index] = Some(Symbol::new(start));
-        } else if let Some(sym) = self.temps[index].take() {
-            self.temps[index] = Some(sym.add());
-        }
-    }
-    fn clear(&mut self, index: usize) {
-        self.is_out_of_bound(index);
-        if let Some(it) = self.temps[index].take() {
-            self.contents.push(it);
-        }
-    }
-    fn is_out_of_bound(&self, index: usize) {
-        if index > 2 {
-            panic!("Symbol index must be between 0, 1, or 2");
-        }
-    }
+// #################################################################################
+// gear rules
+
+// how many numbers a symbol has to touch to count
+#[derive(Debug, Clone, Copy)]
+enum Count {
+    Exactly(usize),
+    AtLeast(usize),
 }
 
-type Gear = Symbol;
-impl Container<Gear, 1> {
-    fn new() -> Self {
-        Container {
-            temps: [None],
-            contents: Vec::new(),
-        }
-    }
-    fn update(&mut self, start: usize) {
-        if self.temps[0].is_none() {
-            self.temps[0] = Some(Symbol::new(start));
-        } else if let Some(gear) = self.temps[0].take() {
-            self.temps[0] = Some(gear.add());
-        }
-    }
-    fn clear(&mut self) {
-        if let Some(it) = self.temps[0].take() {
-            self.contents.push(it);
-        }
-    }
+// how the touching numbers are folded into one value
+#[derive(Debug, Clone, Copy)]
+enum Combine {
+    Product,
+    Sum,
+    Max,
 }
-// ##########################################################################
-// main
-// part one
-fn find_char_part_one(ch: Option<char>) -> Char {
-    match ch.map(|c| c.to_ascii_lowercase()) {
-        Some('0'..='9') => Char::Digit(ch.unwrap() as u8 - b'0'),
-        Some('.') => Char::Other,
-        _ => Char::Important,
-    }
+
+// written as `<symbol><'=' or '>='><count>:<product, sum or max>`,
+// e.g. `*=2:product` or `#>=3:sum`
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    symbol: char,
+    count: Count,
+    combine: Combine,
 }
 
-fn part_one(lines: &Lines) -> u32 {
-    if lines.middle.is_none() {
-        return 0;
-    }
-    let mut numbers = Container::<Number, 1>::new();
-    let mut symbols = Container::<Symbol, 3>::new();
-    for (i, ch_above, ch_middle, ch_below) in lines.iter() {
-        // above
-        match find_char_part_one(ch_above) {
-            Char::Important => symbols.update(0, i),
-            _ => symbols.clear(0),
-        }
-        // middle
-        match find_char_part_one(ch_middle) {
-            Char::Digit(digit) => {
-                numbers.update(i, digit);
-                symbols.clear(1);
-            }
-            Char::Important => {
-                symbols.update(1, i);
-                numbers.clear();
-            }
-            Char::Other => {
-                numbers.clear();
-                symbols.clear(1);
-            }
-        }
-        // below
-        match find_char_part_one(ch_below) {
-            Char::Important => symbols.update(2, i),
-            _ => symbols.clear(2),
-        }
-    }
-    // loop over all numbers
-    let mut subtotal = 0;
-    for num in &numbers.contents {
-        for sym in &symbols.contents {
-            if sym.end() >= num.start && num.end() >= sym.start {
-                subtotal += num.value;
-            }
+impl Rule {
+    fn parse(text: &str) -> Result<Self, Invalid<'static>> {
+        let mut chars = text.chars();
+        let symbol = chars.next().ok_or(Invalid::Rule("Empty rule"))?;
+        let (condition, combine) = chars
+            .as_str()
+            .split_once(':')
+            .ok_or(Invalid::Rule("The rule does not contain ':'"))?;
+        let count = if let Some(n) = condition.strip_prefix(">=") {
+            Count::AtLeast(n.parse().map_err(|_| Invalid::Rule("Invalid count"))?)
+        } else if let Some(n) = condition.strip_prefix('=') {
+            Count::Exactly(n.parse().map_err(|_| Invalid::Rule("Invalid count"))?)
+        } else {
+            return Err(Invalid::Rule("The count must start with '=' or '>='"));
+        };
+        let combine = match combine {
+            "product" => Combine::Product,
+            "sum" => Combine::Sum,
+            "max" => Combine::Max,
+            _ => return Err(Invalid::Rule("Use 'product', 'sum' or 'max'")),
+        };
+        Ok(Rule {
+            symbol,
+            count,
+            combine,
+        })
+    }
+    // None if the rule does not apply, Some(None) if the value overflows
+    fn value(&self, numbers: &[&Number]) -> Option<Option<u64>> {
+        let qualifies = match self.count {
+            Count::Exactly(n) => numbers.len() == n,
+            Count::AtLeast(n) => numbers.len() >= n,
+        };
+        if !qualifies {
+            return None;
         }
+        let mut values = numbers.iter().map(|num| num.value);
+        Some(match self.combine {
+            Combine::Product => values.try_fold(1u64, |acc, value| acc.checked_mul(value)),
+            Combine::Sum => values.try_fold(0u64, |acc, value| acc.checked_add(value)),
+            Combine::Max => Some(values.max().unwrap_or(0)),
+        })
     }
-    return subtotal;
 }
 
-// part two
-fn find_char_part_two(ch: Option<char>) -> Char {
-    match ch.map(|c| c.to_ascii_lowercase()) {
-        Some('0'..='9') => Char::Digit(ch.unwrap() as u8 - b'0'),
-        Some('*') => Char::Important,
-        _ => Char::Other,
-    }
+// every symbol is checked against the rules for its character; the first
+// rule that matches decides its value
+fn apply_rules(schematic: &Schematic, rules: &[Rule]) -> Result<u64, Invalid<'static>> {
+    schematic
+        .symbols
+        .iter()
+        .enumerate()
+        .filter_map(|(i, sym)| {
+            let numbers = schematic.numbers_of(i);
+            rules
+                .iter()
+                .filter(|rule| rule.symbol == sym.ch)
+                .find_map(|rule| rule.value(&numbers))
+        })
+        .try_fold(0u64, |total, value| total.checked_add(value?))
+        .ok_or(Invalid::Overflow("The sum of the symbol values"))
 }
 
-fn part_two(lines: &Lines) -> u32 {
-    if lines.middle.is_none() {
-        return 0;
+// #################################################################################
+// export
+
+// escapes a symbol so it can sit inside a double quoted DOT or JSON string
+fn escape(ch: char) -> String {
+    match ch {
+        '"' | '\\' => format!("\\{}", ch),
+        _ => ch.to_string(),
     }
-    let mut numbers = Container::<Number, 3>::new();
-    let mut gears = Container::<Gear, 1>::new();
-    for (i, ch_above, ch_middle, ch_below) in lines.iter() {
-        // above
-        match find_char_part_two(ch_above) {
-            Char::Digit(digit) => numbers.update(0, i, digit),
-            _ => numbers.clear(0),
-        }
-        // middle
-        match find_char_part_two(ch_middle) {
-            Char::Digit(digit) => {
-                numbers.update(1, i, digit);
-                gears.clear();
-            }
-            Char::Important => {
-                gears.update(i);
-                numbers.clear(1);
-            }
-            Char::Other => {
-                numbers.clear(1);
-                gears.clear();
-            }
-        }
-        // below
-        match find_char_part_two(ch_below) {
-            Char::Digit(digit) => numbers.update(2, i, digit),
-            _ => numbers.clear(2),
+}
+
+impl Schematic {
+    // numbers are boxes, symbols are circles; `pos` pins every node to its
+    // column and row so `neato` keeps the layout of the schematic
+    fn to_dot(&self) -> String {
+        let mut out = String::from("graph schematic {\n");
+        for (i, num) in self.numbers.iter().enumerate() {
+            out.push_str(&format!(
+                "    n{} [shape=box, label=\"{}\\n({},{}..{})\", pos=\"{},{}!\"];\n",
+                i,
+                num.value,
+                num.row,
+                num.start,
+                num.end,
+                num.start,
+                -(num.row as i64)
+            ));
         }
-    }
-    // loop over all numbers
-    let mut subtotal = 0;
-    let mut gear_numbers = Vec::new();
-    for gear in &gears.contents {
-        for num in &numbers.contents {
-            if gear.end() >= num.start && num.end() >= gear.start {
-                gear_numbers.push(num.value);
-            }
+        for (i, sym) in self.symbols.iter().enumerate() {
+            out.push_str(&format!(
+                "    s{} [shape=circle, label=\"{}\\n({},{})\", pos=\"{},{}!\"];\n",
+                i,
+                escape(sym.ch),
+                sym.row,
+                sym.col,
+                sym.col,
+                -(sym.row as i64)
+            ));
         }
-        if gear_numbers.len() == 2 {
-            subtotal += gear_numbers[0] * gear_numbers[1];
+        for (num, sym) in self.edges() {
+            out.push_str(&format!("    n{} -- s{};\n", num, sym));
         }
-        gear_numbers.clear();
+        out.push_str("}\n");
+        out
+    }
+    fn to_json(&self) -> String {
+        let numbers: Vec<String> = self
+            .numbers
+            .iter()
+            .enumerate()
+            .map(|(i, num)| {
+                format!(
+                    "{{\"id\":{},\"value\":{},\"row\":{},\"start\":{},\"end\":{}}}",
+                    i, num.value, num.row, num.start, num.end
+                )
+            })
+            .collect();
+        let symbols: Vec<String> = self
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(i, sym)| {
+                format!(
+                    "{{\"id\":{},\"symbol\":\"{}\",\"row\":{},\"col\":{}}}",
+                    i,
+                    escape(sym.ch),
+                    sym.row,
+                    sym.col
+                )
+            })
+            .collect();
+        let edges: Vec<String> = self
+            .edges()
+            .map(|(num, sym)| format!("{{\"number\":{},\"symbol\":{}}}", num, sym))
+            .collect();
+        format!(
+            "{{\"numbers\":[{}],\"symbols\":[{}],\"edges\":[{}]}}",
+            numbers.join(","),
+            symbols.join(","),
+            edges.join(",")
+        )
     }
-    return subtotal;
 }
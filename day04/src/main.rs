@@ -1,13 +1,35 @@
+use std::collections::{HashSet, VecDeque};
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::path::PathBuf;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Invalid<'a> {
     Arg(&'a str),
+    // line number (1-based) and what is wrong with it
+    Card(usize, Problem),
+    // strict mode found this many problems
+    Strict(usize),
+    // the points of the card with this id, or the total, do not fit in a u64
+    Points(Option<usize>),
+    // the copies of the card with this id, or the total, do not fit in a u64
+    Copies(Option<usize>),
 }
 
+#[allow(dead_code)]
+#[derive(Debug)]
+enum Problem {
+    NoColon,
+    NoId,
+    NoSeparator,
+    Token(String),
+    Duplicate(u32),
+    Order { expected: usize, found: usize },
+}
+
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
@@ -19,6 +41,7 @@ enum Error<'a> {
 enum Case {
     One,
     Two,
+    Breakdown,
 }
 
 fn main() -> Result<(), Error<'static>> {
@@ -37,17 +60,45 @@ fn main() -> Result<(), Error<'static>> {
         .map(|arg| match arg.as_str() {
             "1" => Ok(Case::One),
             "2" => Ok(Case::Two),
-            _ => Err(Error::Inv(Invalid::Arg("Invalid argument. Use '1' or '2'"))),
+            "breakdown" => Ok(Case::Breakdown),
+            _ => Err(Error::Inv(Invalid::Arg(
+                "Invalid argument. Use '1', '2' or 'breakdown'",
+            ))),
         })
-        .unwrap_or_else(|| Err(Error::NoArg("Missing argument. Use '1' or '2'")))?;
-    let mut total = 0;
-    let mut bonus = Vec::new();
+        .unwrap_or_else(|| {
+            Err(Error::NoArg(
+                "Missing argument. Use '1', '2' or 'breakdown'",
+            ))
+        })?;
+    let mut strict = false;
+    let mut points = Points::Doubling;
+    let mut window = Window::Next;
+    for arg in args.iter().skip(2) {
+        if arg == "--strict" {
+            strict = true;
+        } else if let Some(value) = arg.strip_prefix("--points=") {
+            points = Points::parse(value)?;
+        } else if let Some(value) = arg.strip_prefix("--copies=") {
+            window = Window::parse(value)?;
+        } else {
+            return Err(Error::Inv(Invalid::Arg(
+                "Invalid flag. Use '--strict', '--points=<doubling|linear|table:a,b,..>' or '--copies=<next|wrap>'",
+            )));
+        }
+    }
+    let mut cards = Vec::new();
+    let mut problems = Vec::new();
+    let mut line_number = 0;
     loop {
         match line_reader.read() {
-            Ok(Some(line)) => match &case {
-                &Case::One => total += part_one(line),
-                &Case::Two => total += part_two(line, &mut bonus),
-            },
+            Ok(Some(line)) => {
+                line_number += 1;
+                match parse_card(&line) {
+                    Ok(card) => cards.push((line_number, card)),
+                    Err(problem) if strict => problems.push((line_number, problem)),
+                    Err(problem) => return Err(Error::Inv(Invalid::Card(line_number, problem))),
+                }
+            }
             Ok(None) => {
                 // End of file reached
                 break;
@@ -59,7 +110,30 @@ fn main() -> Result<(), Error<'static>> {
         }
     }
 
-    println!("Total: {}", total);
+    if strict {
+        let broken = problems.iter().map(|(line, _)| *line).collect::<Vec<_>>();
+        problems.extend(validate(&cards, &broken));
+        problems.sort_by_key(|(line, _)| *line);
+        for (line, problem) in problems.iter() {
+            eprintln!("line {}: {:?}", line, problem);
+        }
+        if !problems.is_empty() {
+            return Err(Error::Inv(Invalid::Strict(problems.len())));
+        }
+    }
+    let cards: Vec<Card> = cards.into_iter().map(|(_, card)| card).collect();
+
+    match case {
+        Case::One => println!("Total: {}", part_one(&cards, &points).map_err(Error::Inv)?),
+        Case::Two => println!("Total: {}", part_two(&cards, &window).map_err(Error::Inv)?),
+        Case::Breakdown => {
+            let results = breakdown(&cards, &window).map_err(Error::Inv)?;
+            println!("card  matches  copies");
+            for card in results {
+                println!("{:>4}  {:>7}  {:>6}", card.id, card.matches, card.copies);
+            }
+        }
+    }
 
     Ok(())
 }
@@ -73,7 +147,7 @@ struct Line {
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -92,69 +166,251 @@ impl Line {
 // #################################################################################
 // #################################################################################
 
-// enum
-
 // struct
+struct Card {
+    id: usize,
+    winning: Vec<u32>,
+    owned: Vec<u32>,
+}
 
-// main
-// part one
-fn part_one(line: String) -> u32 {
-    let cards = *line
-        .split(':')
-        .collect::<Vec<&str>>()
-        .get(1)
-        .expect("No games!");
-    let splitted_cards = cards.split('|').collect::<Vec<&str>>();
-    let [winning_cards, your_cards] = [splitted_cards[0], splitted_cards[1]];
-    let mut total = 0;
-    for winning_card in winning_cards.split_ascii_whitespace() {
-        for your_card in your_cards.split_ascii_whitespace() {
-            if winning_card == your_card {
-                if total == 0 {
-                    total = 1;
-                } else {
-                    total *= 2;
+impl Card {
+    fn matches(&self) -> usize {
+        let winning: HashSet<&u32> = self.winning.iter().collect();
+        let owned: HashSet<&u32> = self.owned.iter().collect();
+        winning.intersection(&owned).count()
+    }
+    // numbers listed more than once on the same side of the '|'
+    fn duplicates(&self) -> Vec<u32> {
+        let mut duplicates = Vec::new();
+        for numbers in [&self.winning, &self.owned] {
+            let mut seen = HashSet::new();
+            for &n in numbers.iter() {
+                if !seen.insert(n) && !duplicates.contains(&n) {
+                    duplicates.push(n);
                 }
             }
         }
+        duplicates
     }
-    total
 }
 
-fn pop_first<T>(vector: &mut Vec<T>, default: T) -> T {
-    if vector.len() == 0 {
-        default
-    } else {
-        vector.remove(0)
+fn parse_card(line: &str) -> Result<Card, Problem> {
+    let (name, numbers) = line.split_once(':').ok_or(Problem::NoColon)?;
+    let id = match name.split_ascii_whitespace().collect::<Vec<&str>>()[..] {
+        ["Card", id] => id.parse().map_err(|_| Problem::NoId)?,
+        _ => return Err(Problem::NoId),
+    };
+    let (winning, owned) = numbers.split_once('|').ok_or(Problem::NoSeparator)?;
+    let parse = |numbers: &str| -> Result<Vec<u32>, Problem> {
+        numbers
+            .split_ascii_whitespace()
+            .map(|n| n.parse().map_err(|_| Problem::Token(n.to_string())))
+            .collect()
+    };
+    Ok(Card {
+        id,
+        winning: parse(winning)?,
+        owned: parse(owned)?,
+    })
+}
+
+// the checks that need more than one line: duplicated numbers, and card ids
+// that have to run 1, 2, 3, ... for the copies in part two to land on the
+// right cards. Every line in `broken` already failed to parse; it still
+// counts as one card, so the card after it is not reported as well.
+fn validate(cards: &[(usize, Card)], broken: &[usize]) -> Vec<(usize, Problem)> {
+    let mut problems = Vec::new();
+    let mut expected = 1;
+    let mut previous = 0;
+    for (line, card) in cards {
+        expected += broken
+            .iter()
+            .filter(|&&b| previous < b && b < *line)
+            .count();
+        previous = *line;
+        for n in card.duplicates() {
+            problems.push((*line, Problem::Duplicate(n)));
+        }
+        if card.id != expected {
+            problems.push((
+                *line,
+                Problem::Order {
+                    expected,
+                    found: card.id,
+                },
+            ));
+        }
+        expected = card.id + 1;
     }
+    problems
 }
 
-// part two
-fn part_two(line: String, bonus: &mut Vec<usize>) -> u32 {
-    let cards = *line
-        .split(':')
-        .collect::<Vec<&str>>()
-        .get(1)
-        .expect("No games!");
-    let splitted_cards = cards.split('|').collect::<Vec<&str>>();
-    let [winning_cards, your_cards] = [splitted_cards[0], splitted_cards[1]];
-    let mut matching: usize = 0;
-    for winning_card in winning_cards.split_ascii_whitespace() {
-        for your_card in your_cards.split_ascii_whitespace() {
-            if winning_card == your_card {
-                matching += 1;
+// how one card did: its matching numbers and how many copies of it we ended up with
+struct CardResult {
+    id: usize,
+    matches: usize,
+    copies: u64,
+}
+
+// how many points a card with n matches is worth
+enum Points {
+    // 1, 2, 4, 8, ...
+    Doubling,
+    // 1, 2, 3, 4, ...
+    Linear,
+    // looked up by the number of matches, the last entry covers anything beyond
+    Table(Vec<u64>),
+}
+
+impl Points {
+    fn parse(text: &str) -> Result<Self, Error<'static>> {
+        match text {
+            "doubling" => Ok(Points::Doubling),
+            "linear" => Ok(Points::Linear),
+            _ => {
+                let table = text
+                    .strip_prefix("table:")
+                    .ok_or(Error::Inv(Invalid::Arg(
+                        "Invalid points. Use 'doubling', 'linear' or 'table:a,b,..'",
+                    )))?
+                    .split(',')
+                    .map(|n| n.trim().parse::<u64>())
+                    .collect::<Result<Vec<u64>, _>>()
+                    .map_err(|_| Error::Inv(Invalid::Arg("Invalid points table")))?;
+                if table.is_empty() {
+                    return Err(Error::Inv(Invalid::Arg("Empty points table")));
+                }
+                Ok(Points::Table(table))
             }
         }
     }
-    let total = 1 + pop_first(bonus, 0);
-    for i in 0..matching {
-        if let Some(value) = bonus.get_mut(i) {
-            *value += total; 
-        } else {
-            bonus.push(total); 
+    // None once doubling runs past a u64
+    fn score(&self, matches: usize) -> Option<u64> {
+        match self {
+            Points::Doubling => match matches {
+                0 => Some(0),
+                n => 1u64.checked_shl(u32::try_from(n - 1).ok()?),
+            },
+            Points::Linear => Some(matches as u64),
+            Points::Table(table) => Some(*table.get(matches).unwrap_or(&table[table.len() - 1])),
+        }
+    }
+}
+
+// which cards a win hands out copies of
+enum Window {
+    // the next n cards, anything past the last card is lost
+    Next,
+    // the next n cards, continuing from the first card after the last one
+    // but stopping before the winning card, so no card is copied twice by
+    // one win. Wrapped copies land on cards that were already scored, so they
+    // are counted but do not win any more copies themselves.
+    Wrap,
+}
+
+impl Window {
+    fn parse(text: &str) -> Result<Self, Error<'static>> {
+        match text {
+            "next" => Ok(Window::Next),
+            "wrap" => Ok(Window::Wrap),
+            _ => Err(Error::Inv(Invalid::Arg(
+                "Invalid copies. Use 'next' or 'wrap'",
+            ))),
         }
     }
-    println!("matching: {}, total = {}", matching, total);
-    println!("bonus: {:?}", bonus);
-    total as u32
+}
+
+// main
+// part one
+fn part_one(cards: &[Card], points: &Points) -> Result<u64, Invalid<'static>> {
+    cards.iter().try_fold(0u64, |total, card| {
+        let score = points
+            .score(card.matches())
+            .ok_or(Invalid::Points(Some(card.id)))?;
+        total.checked_add(score).ok_or(Invalid::Points(None))
+    })
+}
+
+// part two
+// `pending[i]` holds the extra copies already won for the i-th card after the
+// current one. Popping the front and adding to the next `matches` slots keeps
+// every card O(matches).
+fn breakdown(cards: &[Card], window: &Window) -> Result<Vec<CardResult>, Invalid<'static>> {
+    let mut pending: VecDeque<u64> = VecDeque::new();
+    let mut wrapped: Vec<u64> = vec![0; cards.len()];
+    let mut results = Vec::with_capacity(cards.len());
+    for (i, card) in cards.iter().enumerate() {
+        let matches = card.matches();
+        let copies = pending
+            .pop_front()
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(Invalid::Copies(Some(card.id)))?;
+        let ahead = matches.min(cards.len() - i - 1);
+        if pending.len() < ahead {
+            pending.resize(ahead, 0);
+        }
+        for (k, extra) in pending.iter_mut().take(ahead).enumerate() {
+            *extra = extra
+                .checked_add(copies)
+                .ok_or(Invalid::Copies(Some(cards[i + 1 + k].id)))?;
+        }
+        if let Window::Wrap = window {
+            // past the last card the window starts over at the first one
+            for j in 0..(matches - ahead).min(i) {
+                wrapped[j] = wrapped[j]
+                    .checked_add(copies)
+                    .ok_or(Invalid::Copies(Some(cards[j].id)))?;
+            }
+        }
+        results.push(CardResult {
+            id: card.id,
+            matches,
+            copies,
+        });
+    }
+    for (result, extra) in results.iter_mut().zip(wrapped) {
+        result.copies = result
+            .copies
+            .checked_add(extra)
+            .ok_or(Invalid::Copies(Some(result.id)))?;
+    }
+    Ok(results)
+}
+
+fn part_two(cards: &[Card], window: &Window) -> Result<u64, Invalid<'static>> {
+    breakdown(cards, window)?
+        .iter()
+        .try_fold(0u64, |total, card| total.checked_add(card.copies))
+        .ok_or(Invalid::Copies(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copies(lines: &[&str], window: &Window) -> Vec<u64> {
+        let cards = lines
+            .iter()
+            .map(|line| parse_card(line).unwrap())
+            .collect::<Vec<_>>();
+        breakdown(&cards, window)
+            .unwrap()
+            .iter()
+            .map(|card| card.copies)
+            .collect()
+    }
+
+    #[test]
+    fn wrap_stops_before_the_winning_card() {
+        // more matches than cards: the first card's win stays ahead of it,
+        // the last card's win wraps onto the two cards before it only
+        let lines = [
+            "Card 1: 1 2 3 4 5 | 1 2 3 4 5",
+            "Card 2: 1 2 3 4 5 | 6 7 8 9 10",
+            "Card 3: 1 2 3 4 5 | 1 2 3 4 5",
+        ];
+        assert_eq!(copies(&lines, &Window::Next), vec![1, 2, 2]);
+        assert_eq!(copies(&lines, &Window::Wrap), vec![3, 4, 2]);
+    }
 }
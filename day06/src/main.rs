@@ -1,8 +1,9 @@
 use std::env;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::path::PathBuf;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
@@ -21,12 +22,42 @@ fn main() -> Result<(), Error<'static>> {
     // Create a Line instance from a file
     let line_reader = Line::new(file_path).map_err(Error::Io)?;
     match args.get(1) {
-        None => return Err(Error::NoArg("Missing argument. Use '1' or '2'")),
+        None => {
+            return Err(Error::NoArg(
+                "Missing argument. Use '1', '2' or 'explore <1|2> [--summary]'",
+            ))
+        }
         Some(arg) => {
             match arg.as_str() {
                 "1" => part_one(line_reader)?,
                 "2" => part_two(line_reader)?,
-                _ => return Err(Error::InvalidArg("Invalid argument. Use '1' or '2'")),
+                "explore" => {
+                    let races = match args.get(2).map(|arg| arg.as_str()) {
+                        Some("1") => read_file_part_one(line_reader)?,
+                        Some("2") => vec![read_file_part_two(line_reader)?],
+                        _ => {
+                            return Err(Error::InvalidArg(
+                                "Invalid reading. Use 'explore 1' or 'explore 2'",
+                            ))
+                        }
+                    };
+                    // '--summary' leaves out the row for every winning hold time
+                    let summary = match args.get(3).map(|arg| arg.as_str()) {
+                        None => false,
+                        Some("--summary") => true,
+                        Some(_) => {
+                            return Err(Error::InvalidArg(
+                                "Invalid flag. Use 'explore <1|2> --summary'",
+                            ))
+                        }
+                    };
+                    explore(&races, summary).map_err(Error::Io)?;
+                }
+                _ => {
+                    return Err(Error::InvalidArg(
+                        "Invalid argument. Use '1', '2' or 'explore <1|2> [--summary]'",
+                    ))
+                }
             };
         }
     };
@@ -43,7 +74,7 @@ struct Line {
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -156,3 +187,114 @@ fn part_two(line_reader: Line) -> Result<(), Error<'static>> {
     }
     Ok(())
 }
+
+// #################################################################################
+// explore
+
+// the hold times that beat the record, from `first` to `last` inclusive
+struct WinningWindow {
+    first: usize,
+    last: usize,
+}
+
+fn beats(time: usize, record: usize, hold: usize) -> bool {
+    hold as u128 * (time - hold) as u128 > record as u128
+}
+
+fn winning_window(time: usize, record: usize) -> Option<WinningWindow> {
+    // hold * (time - hold) > record between the roots of
+    // hold^2 - time * hold + record, the smaller being
+    // (time - sqrt(time^2 - 4 * record)) / 2. The integer square root is
+    // rounded down, so step to the exact first hold time from there
+    let (t, r) = (time as u128, record as u128);
+    let discriminant = (t * t).checked_sub(4 * r)?;
+    let mut first = ((t - discriminant.isqrt()) / 2) as usize;
+    while first > 0 && beats(time, record, first - 1) {
+        first -= 1;
+    }
+    while first <= time / 2 && !beats(time, record, first) {
+        first += 1;
+    }
+    // the distance is symmetric around time / 2, so the first winning hold time
+    // mirrors the last one
+    if first > time / 2 {
+        return None;
+    }
+    Some(WinningWindow {
+        first,
+        last: time - first,
+    })
+}
+
+// for every race: the winning window, the optimal hold time, and unless
+// `summary` is set, the margin over the record for each winning hold time
+fn explore(races: &[(usize, usize)], summary: bool) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for (i, &(time, record)) in races.iter().enumerate() {
+        writeln!(out, "race {}: time {}, record {}", i + 1, time, record)?;
+        let window = match winning_window(time, record) {
+            Some(window) => window,
+            None => {
+                writeln!(out, "  no hold time beats the record")?;
+                continue;
+            }
+        };
+        let optimal = time / 2;
+        let best = optimal * (time - optimal);
+        writeln!(
+            out,
+            "  winning hold times {}..={} ({} ways), optimal hold {} (distance {}, margin {})",
+            window.first,
+            window.last,
+            window.last - window.first + 1,
+            optimal,
+            best,
+            best - record
+        )?;
+        if summary {
+            continue;
+        }
+        writeln!(
+            out,
+            "  {:>12}  {:>16}  {:>16}",
+            "hold", "distance", "margin"
+        )?;
+        for hold in window.first..=window.last {
+            let distance = hold * (time - hold);
+            writeln!(
+                out,
+                "  {:>12}  {:>16}  {:>16}",
+                hold,
+                distance,
+                distance - record
+            )?;
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_matches_a_scan() {
+        for time in 0..120 {
+            for record in 0..=time * time / 4 + 1 {
+                let scanned = (1..=time / 2).find(|&hold| hold * (time - hold) > record);
+                let window = winning_window(time, record);
+                assert_eq!(
+                    window.as_ref().map(|w| w.first),
+                    scanned,
+                    "{} {}",
+                    time,
+                    record
+                );
+                if let Some(window) = window {
+                    assert_eq!(window.last, time - window.first);
+                }
+            }
+        }
+    }
+}
@@ -4,11 +4,14 @@ use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::path::PathBuf;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
     Io(io::Error),
     InvalidArg(&'a str),
+    InvalidHand(String),
+    Mismatch(String),
 }
 
 fn main() -> Result<(), Error<'static>> {
@@ -21,16 +24,45 @@ fn main() -> Result<(), Error<'static>> {
 
     // Create a Line instance from a file
     let line_reader = Line::new(file_path).map_err(Error::Io)?;
-    match args.get(1) {
+    let mut rules = match args.get(1) {
         None => return Err(Error::NoArg("Missing argument. Use '1' or '2'")),
-        Some(arg) => {
-            match arg.as_str() {
-                "1" => part_one(line_reader)?,
-                "2" => part_two(line_reader)?,
-                _ => return Err(Error::InvalidArg("Invalid argument. Use '1' or '2'")),
-            };
-        }
+        Some(arg) => match arg.as_str() {
+            "1" => Rules::part_one(),
+            "2" => Rules::part_two(),
+            _ => return Err(Error::InvalidArg("Invalid argument. Use '1' or '2'")),
+        },
     };
+    // the preset of the part can be tweaked with
+    // --order=<cards from high to low> --wild=<cards> --size=<cards per hand>
+    // --verify compares the wild card evaluator against the brute force, while
+    // --explain lists every hand with how it got its rank
+    let mut verify = false;
+    let mut explain = false;
+    for arg in args.iter().skip(2) {
+        if arg == "--verify" {
+            verify = true;
+        } else if arg == "--explain" {
+            explain = true;
+        } else if let Some(order) = arg.strip_prefix("--order=") {
+            rules.order = order.chars().rev().collect();
+        } else if let Some(wild) = arg.strip_prefix("--wild=") {
+            rules.wild = wild.chars().collect();
+        } else if let Some(size) = arg.strip_prefix("--size=") {
+            rules.hand_size = size
+                .parse()
+                .map_err(|_| Error::InvalidArg("Invalid hand size"))?;
+        } else {
+            return Err(Error::InvalidArg(
+                "Invalid flag. Use '--order=<cards>', '--wild=<cards>', '--size=<n>', '--verify' or '--explain'",
+            ));
+        }
+    }
+    rules.check()?;
+    if verify {
+        verify_wild(&rules)?;
+    } else {
+        play(line_reader, &rules, explain)?;
+    }
 
     Ok(())
 }
@@ -44,7 +76,7 @@ struct Line {
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -61,32 +93,39 @@ impl Line {
 
 // ##################################################################################
 
-fn determine_type(cards: &str) -> Type {
-    let sets = create_sets(cards);
-    if sets.len() == 1 {
-        return Type::FiveOfAKind;
-    }
-    if sets.len() == 2 {
-        if sets[0].len == 1 {
-            return Type::FourOfAKind;
-        }
-        if sets[0].len == 2 {
-            return Type::FullHouse;
-        }
-    }
-    if sets.len() == 3 {
-        if sets[1].len == 1 {
-            return Type::ThreeOfAKind;
-        }
-        if sets[1].len == 2 {
-            return Type::TwoPair;
+// the sizes of the groups of equal cards, largest first, e.g. [3, 2] for a full house
+fn signature(cards: &str) -> Vec<usize> {
+    create_sets(cards).iter().rev().map(|set| set.len).collect()
+}
+
+// a hand has a type when its largest groups are at least as big as the
+// groups of the pattern, checked from the strongest type down. New types only
+// need a variant and a row here.
+const TYPES: [(Type, &[usize]); 7] = [
+    (Type::FiveOfAKind, &[5]),
+    (Type::FourOfAKind, &[4]),
+    (Type::FullHouse, &[3, 2]),
+    (Type::ThreeOfAKind, &[3]),
+    (Type::TwoPair, &[2, 2]),
+    (Type::OnePair, &[2]),
+    (Type::HighCard, &[]),
+];
+
+fn classify(signature: &[usize]) -> Type {
+    for (typ, pattern) in TYPES {
+        if pattern.len() <= signature.len()
+            && pattern.iter().zip(signature.iter()).all(|(p, s)| s >= p)
+        {
+            return typ;
         }
     }
-    if sets.len() == 4 {
-        return Type::OnePair;
-    }
     Type::HighCard
 }
+
+fn determine_type(cards: &str) -> Type {
+    classify(&signature(cards))
+}
+
 fn create_sets(cards: &str) -> Vec<Set> {
     let mut sets: Vec<Set> = Vec::new();
     'outer: for card in cards.chars() {
@@ -103,7 +142,7 @@ fn create_sets(cards: &str) -> Vec<Set> {
 }
 
 // enum
-#[derive(PartialEq, PartialOrd, Ord, Eq, Debug)]
+#[derive(PartialEq, PartialOrd, Ord, Eq, Debug, Clone, Copy)]
 enum Type {
     HighCard,
     OnePair,
@@ -129,153 +168,75 @@ impl Set {
 }
 
 // ########################################################################################3
-// part one
-
-fn read_file_one(mut line_reader: Line) -> Result<Vec<HandOne>, Error<'static>> {
-    let mut hands = Vec::new();
-    loop {
-        match line_reader.read() {
-            Ok(Some(line)) => {
-                let splitted = line.split_ascii_whitespace().collect::<Vec<_>>();
-                if splitted.len() != 2 {
-                    panic!("The line's len() is not 2!");
-                }
-                let cards = splitted[0];
-                let bid = splitted[1].parse::<usize>().expect("Failed to parse");
-                hands.push(HandOne::new(cards, bid));
-            }
-            Ok(None) => {
-                // End of file reached
-                break;
-            }
-            Err(e) => {
-                eprintln!("Error reading line: {}", e);
-                return Err(Error::Io(e));
-            }
-        }
-    }
-    Ok(hands)
-}
+// rules
 
-#[derive(Debug, Eq, Ord)]
-struct HandOne {
-    typ: Type,
-    cards: String,
-    bid: usize,
+// everything that differs between the variants of the game
+struct Rules {
+    // card labels from the lowest to the highest
+    order: Vec<char>,
+    // cards that act like whatever card makes the strongest hand
+    wild: Vec<char>,
+    hand_size: usize,
 }
 
-impl HandOne {
-    fn new(cards: &str, bid: usize) -> Self {
-        HandOne {
-            cards: cards.into(),
-            bid,
-            typ: determine_type(cards),
+impl Rules {
+    fn part_one() -> Self {
+        Rules {
+            order: "23456789TJQKA".chars().collect(),
+            wild: Vec::new(),
+            hand_size: 5,
         }
     }
-    fn value(card: &char) -> usize {
-        match *card {
-            'A' => 13,
-            'K' => 12,
-            'Q' => 11,
-            'J' => 10,
-            'T' => 9,
-            '9' => 8,
-            '8' => 7,
-            '7' => 6,
-            '6' => 5,
-            '5' => 4,
-            '4' => 3,
-            '3' => 2,
-            '2' => 1,
-            _ => unreachable!(),
+    fn part_two() -> Self {
+        Rules {
+            order: "J23456789TQKA".chars().collect(),
+            wild: vec!['J'],
+            hand_size: 5,
         }
     }
-}
-
-impl PartialEq for HandOne {
-    fn eq(&self, other: &Self) -> bool {
-        self.typ == other.typ
-            && self
-                .cards
-                .chars()
-                .zip(other.cards.chars())
-                .all(|(ch1, ch2)| HandOne::value(&ch1) == HandOne::value(&ch2))
-    }
-}
-
-impl PartialOrd for HandOne {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.typ.partial_cmp(&other.typ) {
-            Some(Ordering::Equal) => {
-                for (ch1, ch2) in self.cards.chars().zip(other.cards.chars()) {
-                    let v1 = HandOne::value(&ch1);
-                    let v2 = HandOne::value(&ch2);
-                    if v1 != v2 {
-                        return Some(v1.cmp(&v2));
-                    }
-                }
-                // Equal
-                return Some(Ordering::Equal);
-            }
-            result => result,
+    fn check(&self) -> Result<(), Error<'static>> {
+        if self.wild.iter().any(|card| !self.order.contains(card)) {
+            return Err(Error::InvalidArg("Every wild card must be in the order"));
+        }
+        if self.hand_size == 0 {
+            return Err(Error::InvalidArg("The hand size must be at least 1"));
         }
+        Ok(())
     }
-}
-
-fn part_one(line_reader: Line) -> Result<(), Error<'static>> {
-    let mut hands = read_file_one(line_reader)?;
-    hands.sort();
-    let mut total = 0;
-    for (i, hand) in hands.iter().enumerate() {
-        println!("Rank: {} {:?}", i + 1, hand);
-        total += hand.bid * (i + 1);
+    fn value(&self, card: char) -> Option<usize> {
+        self.order.iter().position(|&c| c == card)
     }
-    println!("total = {}", total);
-    Ok(())
-}
-
-// ########################################################################################3
-// part two
-fn read_file_two(mut line_reader: Line) -> Result<Vec<HandTwo>, Error<'static>> {
-    let mut hands = Vec::new();
-    loop {
-        match line_reader.read() {
-            Ok(Some(line)) => {
-                let splitted = line.split_ascii_whitespace().collect::<Vec<_>>();
-                if splitted.len() != 2 {
-                    panic!("The line's len() is not 2!");
-                }
-                let cards = splitted[0];
-                let bid = splitted[1].parse::<usize>().expect("Failed to parse");
-                hands.push(HandTwo::new(cards, bid));
-            }
-            Ok(None) => {
-                // End of file reached
-                break;
-            }
-            Err(e) => {
-                eprintln!("Error reading line: {}", e);
-                return Err(Error::Io(e));
-            }
-        }
+    fn is_wild(&self, card: char) -> bool {
+        self.wild.contains(&card)
+    }
+    // the cards a wild card may stand for
+    fn substitutes(&self) -> Vec<char> {
+        self.order
+            .iter()
+            .copied()
+            .filter(|&card| !self.is_wild(card))
+            .collect()
     }
-    Ok(hands)
 }
 
-// const CARDS: [char; 12] = ['A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2'];
+// ########################################################################################3
+// hands
 
+// counts through every way to fill `len` slots with one of `base` cards
 struct Combination {
     current: Vec<usize>,
     len: usize,
+    base: usize,
     overflow: bool,
 }
 
 impl Combination {
-    fn new(len: usize) -> Self {
-        let overflow = len == 0;
+    fn new(len: usize, base: usize) -> Self {
+        let overflow = len == 0 || base == 0;
         Combination {
             current: vec![0; len],
             len,
+            base,
             overflow,
         }
     }
@@ -289,7 +250,7 @@ impl Iterator for Combination {
         if !self.overflow {
             for i in 0..self.len {
                 let added = self.current[i] + 1;
-                if added > 12 {
+                if added >= self.base {
                     self.current[i] = 0;
                     continue;
                 }
@@ -305,125 +266,397 @@ impl Iterator for Combination {
     }
 }
 
-fn determine_type_further(cards: &str) -> Type {
+// every wild card joins the largest group of the other cards, which is never
+// worse than any other substitution. All wild hands become the highest card.
+fn best_substitution(cards: &str, rules: &Rules) -> String {
+    let others = cards
+        .chars()
+        .filter(|&c| !rules.is_wild(c))
+        .collect::<String>();
+    let sets = create_sets(&others);
+    let target = match sets.last() {
+        Some(set) => set.card,
+        None => match rules.substitutes().last() {
+            Some(&card) => card,
+            None => return cards.into(),
+        },
+    };
+    cards
+        .chars()
+        .map(|c| if rules.is_wild(c) { target } else { c })
+        .collect()
+}
+
+fn determine_type_further(cards: &str, rules: &Rules) -> Type {
+    determine_type(&best_substitution(cards, rules))
+}
+
+// tries every substitution for the wild cards, kept to check the evaluator above
+fn determine_type_brute_force(cards: &str, rules: &Rules) -> Type {
     let mut current_type = determine_type(cards);
     let mut cards_char = cards.chars().collect::<Vec<_>>();
     let indices = cards
         .chars()
         .enumerate()
-        .filter(|&(_, c)| c == 'J')
+        .filter(|&(_, c)| rules.is_wild(c))
         .map(|(i, _)| i)
         .collect::<Vec<_>>();
-    let len = indices.len();
-    let combination = Combination::new(len);
+    let substitutes = rules.substitutes();
+    let combination = Combination::new(indices.len(), substitutes.len());
     for com in combination {
+        // swapping what two wild cards stand for gives the same type, so only
+        // sorted substitutions are tried
+        if com.windows(2).any(|pair| pair[0] > pair[1]) {
+            continue;
+        }
         for (index, new_char) in indices.iter().zip(com.iter()) {
-            cards_char[*index] = HandTwo::inv_value(new_char);
+            cards_char[*index] = substitutes[*new_char];
         }
         let new_cards = cards_char.iter().collect::<String>();
         let new_type = determine_type(&new_cards);
         if new_type > current_type {
             current_type = new_type;
         }
-        if current_type == Type::FiveOfAKind {
-            break;
-        }
     }
     current_type
 }
 
-#[derive(Debug, Eq, Ord)]
-struct HandTwo {
+// every multiset of `hand_size` cards, each in sorted order. The type only
+// depends on which cards are in the hand, not their order, so these cover
+// all hands
+fn multisets(rules: &Rules) -> Vec<String> {
+    let mut hands = Vec::new();
+    let mut hand = vec![0; rules.hand_size];
+    loop {
+        hands.push(hand.iter().map(|&i| rules.order[i]).collect::<String>());
+        // next non decreasing sequence of card indices
+        match hand.iter().rposition(|&i| i + 1 < rules.order.len()) {
+            Some(pos) => {
+                let next = hand[pos] + 1;
+                for i in hand.iter_mut().skip(pos) {
+                    *i = next;
+                }
+            }
+            None => break,
+        }
+    }
+    hands
+}
+
+// the same check as the tests, for rules given on the command line
+fn verify_wild(rules: &Rules) -> Result<(), Error<'static>> {
+    let hands = multisets(rules);
+    for cards in hands.iter() {
+        let fast = determine_type_further(cards, rules);
+        let slow = determine_type_brute_force(cards, rules);
+        if fast != slow {
+            return Err(Error::Mismatch(format!(
+                "{}: evaluator says {:?}, brute force says {:?}",
+                cards, fast, slow
+            )));
+        }
+    }
+    println!("{} hands agree", hands.len());
+    Ok(())
+}
+
+#[derive(Debug, Eq)]
+struct Hand {
     typ: Type,
+    // group sizes after the wild cards were substituted
+    signature: Vec<usize>,
     cards: String,
+    // the cards with every wild card replaced by what it stands for
+    substitution: String,
+    // the rank of every card under the rules, in the order they were dealt
+    values: Vec<usize>,
     bid: usize,
 }
 
-impl HandTwo {
-    fn new(cards: &str, bid: usize) -> Self {
-        HandTwo {
+impl Hand {
+    fn new(cards: &str, bid: usize, rules: &Rules) -> Result<Self, Error<'static>> {
+        if cards.chars().count() != rules.hand_size {
+            return Err(Error::InvalidHand(format!(
+                "{} does not have {} cards",
+                cards, rules.hand_size
+            )));
+        }
+        let values = cards
+            .chars()
+            .map(|card| {
+                rules.value(card).ok_or_else(|| {
+                    Error::InvalidHand(format!("Unknown card {} in {}", card, cards))
+                })
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        let best = best_substitution(cards, rules);
+        Ok(Hand {
+            typ: determine_type(&best),
+            signature: signature(&best),
             cards: cards.into(),
+            substitution: best,
+            values,
             bid,
-            typ: determine_type_further(cards),
-        }
+        })
     }
-
-    fn value(card: &char) -> usize {
-        match *card {
-            'A' => 12,
-            'K' => 11,
-            'Q' => 10,
-            'T' => 9,
-            '9' => 8,
-            '8' => 7,
-            '7' => 6,
-            '6' => 5,
-            '5' => 4,
-            '4' => 3,
-            '3' => 2,
-            '2' => 1,
-            'J' => 0,
-            _ => unreachable!(),
-        }
+    fn key(&self) -> (Type, &[usize]) {
+        (self.typ, &self.values)
     }
-    fn inv_value(value: &usize) -> char {
-        match *value {
-            12 => 'A',
-            11 => 'K',
-            10 => 'Q',
-            9 => 'T',
-            8 => '9',
-            7 => '8',
-            6 => '7',
-            5 => '6',
-            4 => '5',
-            3 => '4',
-            2 => '3',
-            1 => '2',
-            0 => 'J',
-            _ => unreachable!(),
-        }
+    // the position of the first card that tells two hands of the same type apart
+    fn tie_break(&self, other: &Self) -> Option<usize> {
+        self.values
+            .iter()
+            .zip(other.values.iter())
+            .position(|(v1, v2)| v1 != v2)
     }
 }
 
-impl PartialEq for HandTwo {
+// hands are ranked by their type, then by their cards one by one. Everything
+// else (the bid, the substitution) plays no part, so two hands with the same
+// key are equal and keep the order they were read in when sorted.
+impl PartialEq for Hand {
     fn eq(&self, other: &Self) -> bool {
-        self.typ == other.typ
-            && self
-                .cards
-                .chars()
-                .zip(other.cards.chars())
-                .all(|(ch1, ch2)| HandTwo::value(&ch1) == HandTwo::value(&ch2))
+        self.key() == other.key()
     }
 }
 
-impl PartialOrd for HandTwo {
+impl PartialOrd for Hand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.typ.partial_cmp(&other.typ) {
-            Some(Ordering::Equal) => {
-                for (ch1, ch2) in self.cards.chars().zip(other.cards.chars()) {
-                    let v1 = HandTwo::value(&ch1);
-                    let v2 = HandTwo::value(&ch2);
-                    if v1 != v2 {
-                        return Some(v1.cmp(&v2));
-                    }
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+fn read_file(mut line_reader: Line, rules: &Rules) -> Result<Vec<Hand>, Error<'static>> {
+    let mut hands = Vec::new();
+    loop {
+        match line_reader.read() {
+            Ok(Some(line)) => {
+                let splitted = line.split_ascii_whitespace().collect::<Vec<_>>();
+                if splitted.len() != 2 {
+                    panic!("The line's len() is not 2!");
                 }
-                // Equal
-                return Some(Ordering::Equal);
+                let cards = splitted[0];
+                let bid = splitted[1].parse::<usize>().expect("Failed to parse");
+                hands.push(Hand::new(cards, bid, rules)?);
+            }
+            Ok(None) => {
+                // End of file reached
+                break;
+            }
+            Err(e) => {
+                eprintln!("Error reading line: {}", e);
+                return Err(Error::Io(e));
             }
-            result => result,
         }
     }
+    Ok(hands)
 }
 
-fn part_two(line_reader: Line) -> Result<(), Error<'static>> {
-    let mut hands = read_file_two(line_reader)?;
+// ########################################################################################3
+// both parts
+
+fn play(line_reader: Line, rules: &Rules, explain: bool) -> Result<(), Error<'static>> {
+    let mut hands = read_file(line_reader, rules)?;
     hands.sort();
+    if explain {
+        println!(
+            "{:>5}  {:<8}  {:<8}  {:<12}  {:<15}  {:>5}  {:>9}  above",
+            "rank", "cards", "played", "type", "signature", "bid", "winnings"
+        );
+    }
     let mut total = 0;
     for (i, hand) in hands.iter().enumerate() {
-        println!("Rank: {} {:?}", i + 1, hand);
-        total += hand.bid * (i + 1);
+        let winnings = hand.bid * (i + 1);
+        total += winnings;
+        if !explain {
+            continue;
+        }
+        // what put this hand above the one ranked right below it
+        let above = match i.checked_sub(1).map(|j| &hands[j]) {
+            None => "-".to_string(),
+            Some(below) if below.typ != hand.typ => "type".to_string(),
+            Some(below) => match hand.tie_break(below) {
+                Some(pos) => format!("card {}", pos + 1),
+                None => "tie".to_string(),
+            },
+        };
+        println!(
+            "{:>5}  {:<8}  {:<8}  {:<12}  {:<15}  {:>5}  {:>9}  {}",
+            i + 1,
+            hand.cards,
+            hand.substitution,
+            format!("{:?}", hand.typ),
+            format!("{:?}", hand.signature),
+            hand.bid,
+            winnings,
+            above
+        );
     }
     println!("total = {}", total);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift, enough to shuffle hands without pulling in a crate
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+        fn shuffle(&mut self, cards: &str) -> String {
+            let mut cards = cards.chars().collect::<Vec<_>>();
+            for i in (1..cards.len()).rev() {
+                cards.swap(i, self.below(i + 1));
+            }
+            cards.into_iter().collect()
+        }
+    }
+
+    fn assert_wild_agrees(rules: &Rules, orders: usize) {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for sorted in multisets(rules) {
+            let mut hands = vec![sorted.clone()];
+            hands.extend((0..orders).map(|_| rng.shuffle(&sorted)));
+            for cards in hands {
+                assert_eq!(
+                    determine_type_further(&cards, rules),
+                    determine_type_brute_force(&cards, rules),
+                    "{}",
+                    cards
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wild_part_two() {
+        assert_wild_agrees(&Rules::part_two(), 3);
+    }
+
+    #[test]
+    fn wild_several_ranks() {
+        let mut rules = Rules::part_two();
+        rules.wild = vec!['J', '2', 'Q'];
+        assert_wild_agrees(&rules, 1);
+    }
+
+    #[test]
+    fn wild_four_cards() {
+        let mut rules = Rules::part_two();
+        rules.hand_size = 4;
+        assert_wild_agrees(&rules, 3);
+    }
+
+    #[test]
+    fn wild_six_cards() {
+        let mut rules = Rules::part_two();
+        rules.hand_size = 6;
+        assert_wild_agrees(&rules, 0);
+    }
+
+    // hands drawn from only a few labels, so that types, keys and whole
+    // hands repeat often
+    fn random_hands(rules: &Rules, labels: &str, count: usize, seed: u64) -> Vec<Hand> {
+        let labels = labels.chars().collect::<Vec<_>>();
+        let mut rng = Rng(seed);
+        (0..count)
+            .map(|bid| {
+                let cards = (0..rules.hand_size)
+                    .map(|_| labels[rng.below(labels.len())])
+                    .collect::<String>();
+                Hand::new(&cards, bid, rules).unwrap()
+            })
+            .collect()
+    }
+
+    fn generated() -> Vec<(Rules, Vec<Hand>)> {
+        vec![
+            (Rules::part_one(), "AKQJT98765432"),
+            (Rules::part_one(), "AKJ"),
+            (Rules::part_two(), "AKQJT98765432"),
+            (Rules::part_two(), "AJ2"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (rules, labels))| {
+            let hands = random_hands(&rules, labels, 80, 0x2545_f491_4f6c_dd1d + i as u64);
+            (rules, hands)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn order_antisymmetric_and_agrees_with_eq() {
+        for (_, hands) in generated() {
+            for a in hands.iter() {
+                for b in hands.iter() {
+                    assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{} {}", a.cards, b.cards);
+                    assert_eq!(
+                        a == b,
+                        a.cmp(b) == Ordering::Equal,
+                        "{} {}",
+                        a.cards,
+                        b.cards
+                    );
+                    assert_eq!(a.partial_cmp(b), Some(a.cmp(b)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn order_transitive() {
+        for (_, hands) in generated() {
+            for a in hands.iter() {
+                for b in hands.iter().filter(|&b| a <= b) {
+                    for c in hands.iter().filter(|&c| b <= c) {
+                        assert!(a <= c, "{} <= {} <= {}", a.cards, b.cards, c.cards);
+                        if a < b || b < c {
+                            assert!(a < c, "{} < {} < {}", a.cards, b.cards, c.cards);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sort_keeps_equal_hands_in_input_order() {
+        for (rules, mut hands) in generated() {
+            // the same cards again with other bids
+            let twins = hands
+                .iter()
+                .map(|hand| Hand::new(&hand.cards, hand.bid + 1000, &rules).unwrap())
+                .collect::<Vec<_>>();
+            hands.extend(twins);
+            hands.sort();
+            for pair in hands.windows(2) {
+                assert!(pair[0] <= pair[1]);
+                if pair[0] == pair[1] {
+                    assert!(
+                        pair[0].bid < pair[1].bid,
+                        "{} {}",
+                        pair[0].cards,
+                        pair[1].cards
+                    );
+                }
+            }
+        }
+    }
+}
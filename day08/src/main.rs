@@ -1,44 +1,75 @@
-use std::collections::HashSet;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::File;
+use std::hash::Hash;
 use std::io::{self, BufRead, BufReader};
 use std::path::PathBuf;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Invalid<'a> {
     Arg(&'a str),
+    // a node defined on more than one line
+    Duplicate(String),
+    // nodes used as a child but never defined
+    Dangling(Vec<String>),
+    Selector(&'a str),
+    Regex(regex::Error),
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
     Io(io::Error),
     Inv(Invalid<'a>),
+    // the common step does not fit in an i128
+    Overflow(&'a str),
 }
 
+const USAGE: &str = "Use '1', '2', 'dot' or 'product'";
+
 fn main() -> Result<(), Error<'static>> {
     let root_dir = env::current_dir().expect("Failed to get current working directory");
     let args: Vec<String> = env::args().collect();
 
-    match args.get(1) {
-        None => return Err(Error::NoArg("Missing argument. Use '1' or '2'")),
-        Some(arg) => {
-            match arg.as_str() {
-                "1" => {
-                    // let file_path = root_dir.join("src").join("sample-2.txt");
-                    let file_path = root_dir.join("src").join("input.txt");
-                    let line_reader = Line::new(file_path).map_err(Error::Io)?;
-                    part_one(line_reader)?
-                }
-                "2" => {
-                    // let file_path = root_dir.join("src").join("sample-3.txt");
-                    let file_path = root_dir.join("src").join("input.txt");
-                    let line_reader = Line::new(file_path).map_err(Error::Io)?;
-                    part_two(line_reader)?
-                }
-                _ => return Err(Error::Inv(Invalid::Arg("Invalid argument. Use '1' or '2'"))),
-            };
+    let part = match args.get(1) {
+        None => return Err(Error::NoArg(USAGE)),
+        Some(arg) => match arg.as_str() {
+            "1" | "2" | "dot" | "product" => arg.as_str(),
+            _ => return Err(Error::Inv(Invalid::Arg(USAGE))),
+        },
+    };
+    // each part has its own start and end nodes, which can be replaced with
+    // --start=<selector> and --end=<selector>
+    let (mut start, mut end) = match part {
+        "1" => (
+            Selector::Names(vec!["AAA".into()]),
+            Selector::Names(vec!["ZZZ".into()]),
+        ),
+        _ => (Selector::Suffix("A".into()), Selector::Suffix("Z".into())),
+    };
+    for arg in args.iter().skip(2) {
+        if let Some(selector) = arg.strip_prefix("--start=") {
+            start = Selector::parse(selector).map_err(Error::Inv)?;
+        } else if let Some(selector) = arg.strip_prefix("--end=") {
+            end = Selector::parse(selector).map_err(Error::Inv)?;
+        } else {
+            return Err(Error::Inv(Invalid::Arg(
+                "Invalid flag. Use '--start=<selector>' or '--end=<selector>'",
+            )));
         }
+    }
+
+    // let file_path = root_dir.join("src").join("sample-2.txt");
+    // let file_path = root_dir.join("src").join("sample-3.txt");
+    let file_path = root_dir.join("src").join("input.txt");
+    let line_reader = Line::new(file_path).map_err(Error::Io)?;
+    match part {
+        "1" => part_one(line_reader, &start, &end)?,
+        "2" => part_two(line_reader, &start, &end)?,
+        _ => export(line_reader, &start, &end, part == "product")?,
     };
 
     Ok(())
@@ -53,7 +84,7 @@ struct Line {
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -73,56 +104,27 @@ impl Line {
     }
 }
 // #################################################################################
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 enum Instruction {
     Left,
     Right,
 }
 
-// struct Node {
-//     value: String,
-//     left: Option<Box<Node>>,
-//     right: Option<Box<Node>>,
-// }
-
 #[derive(Clone)]
 struct Node {
-    value: u16,
-    string: String,
+    name: String,
     left: Option<usize>,
     right: Option<usize>,
 }
 
 impl Node {
-    fn convert(value: &str) -> u16 {
-        let cs = value
-            .chars()
-            .map(|c| ((c as u8) - ('A' as u8)) as u16)
-            .collect::<Vec<u16>>();
-        let val = cs
-            .into_iter()
-            .enumerate()
-            .reduce(|(_, acc), (i, e)| (i, acc + e * 26_u16.pow(i as u32)))
-            .map(|a| a.1)
-            .expect("Cannot convert it?");
-        val
-    }
-    fn new(value: &str) -> Self {
+    fn new(name: &str) -> Self {
         Node {
-            value: Node::convert(value),
-            string: value.into(),
+            name: name.into(),
             left: None,
             right: None,
         }
     }
-    fn with_children(value: &str, left: usize, right: usize) -> Self {
-        Node {
-            value: Node::convert(value),
-            string: value.into(),
-            left: Some(left),
-            right: Some(right),
-        }
-    }
     fn add_left(&mut self, index: usize) {
         self.left = Some(index);
     }
@@ -133,30 +135,25 @@ impl Node {
 
 impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
-        self.value == other.value
+        self.name == other.name
     }
 }
 
 impl std::fmt::Display for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Node {{ value: {} }}", self.string)
+        write!(f, "Node {{ value: {} }}", self.name)
     }
 }
 
 impl std::fmt::Debug for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.string)
+        write!(f, "{}", self.name)
     }
 }
 
 // function
 
-// fn get_or_create(graph: Graph, value: &str) ->
-
-fn read_file(
-    mut line_reader: Line,
-    graph: &mut impl Graph,
-) -> Result<Vec<Instruction>, Error<'static>> {
+fn read_file(mut line_reader: Line, graph: &mut Graph) -> Result<Vec<Instruction>, Error<'static>> {
     // read first line
     let first_line = line_reader.read().map_err(Error::Io)?;
     let instructions = match first_line {
@@ -175,25 +172,24 @@ fn read_file(
 
     let _ = line_reader.read(); // skip second line
 
-    // let mut graph = GraphTwo::new();
     loop {
         match line_reader.read() {
             Ok(Some(line)) => {
-                let splitted = line.split("=").map(|l| l.trim()).collect::<Vec<_>>();
+                let splitted = line.split('=').map(|l| l.trim()).collect::<Vec<_>>();
                 if splitted.len() != 2 {
                     panic!("Not 2 parts!")
                 }
                 let head = splitted[0];
                 let len = splitted[1].len();
                 let directions = splitted[1][1..len - 1]
-                    .split(",")
+                    .split(',')
                     .map(|s| s.trim())
                     .collect::<Vec<_>>();
                 if directions.len() != 2 {
                     panic!("No directions??")
                 }
                 let [left, right] = [directions[0], directions[1]];
-                graph.add(head, left, right);
+                graph.add(head, left, right).map_err(Error::Inv)?;
             }
             Ok(None) => {
                 // End of file reached
@@ -205,346 +201,505 @@ fn read_file(
             }
         }
     }
+    graph.validate().map_err(Error::Inv)?;
     Ok(instructions)
 }
 
-// part one
-struct GraphOne {
+// nodes live in a vector and point at each other by index; names are looked
+// up through a hash map, so building the graph is linear in its size
+struct Graph {
     nodes: Vec<Node>,
+    index: HashMap<String, usize>,
 }
 
-trait Graph {
-    fn get(&self, value: &str) -> Option<&Node>;
-    fn get_index(&self, value: &str) -> Option<usize>;
-    fn add(&mut self, head: &str, left: &str, right: &str);
-}
-
-impl GraphOne {
+impl Graph {
     fn new() -> Self {
-        GraphOne { nodes: Vec::new() }
+        Graph {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+    fn get_index(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+    fn get_or_insert(&mut self, name: &str) -> usize {
+        if let Some(i) = self.get_index(name) {
+            return i;
+        }
+        self.nodes.push(Node::new(name));
+        self.index.insert(name.into(), self.nodes.len() - 1);
+        self.nodes.len() - 1
     }
+    fn add(&mut self, head: &str, left: &str, right: &str) -> Result<(), Invalid<'static>> {
+        let left_index = self.get_or_insert(left);
+        let right_index = self.get_or_insert(right);
+        let head_index = self.get_or_insert(head);
+        let node = &mut self.nodes[head_index];
+        if node.left.is_some() {
+            return Err(Invalid::Duplicate(head.into()));
+        }
+        node.add_left(left_index);
+        node.add_right(right_index);
+        Ok(())
+    }
+    // every node that shows up as a child has to be defined on its own line
+    fn validate(&self) -> Result<(), Invalid<'static>> {
+        let dangling = self
+            .nodes
+            .iter()
+            .filter(|n| n.left.is_none())
+            .map(|n| n.name.clone())
+            .collect::<Vec<_>>();
+        if dangling.is_empty() {
+            Ok(())
+        } else {
+            Err(Invalid::Dangling(dangling))
+        }
+    }
+}
+
+// which nodes to start from or to stop at, written as `names:AAA,BBB`,
+// `prefix:X`, `suffix:Z` or `regex:^[A-C]+$`
+enum Selector {
+    Names(Vec<String>),
+    Prefix(String),
+    Suffix(String),
+    Regex(Regex),
 }
 
-impl Graph for GraphOne {
-    fn get(&self, value: &str) -> Option<&Node> {
-        self.nodes.iter().find(|&n| n.value == Node::convert(value))
+impl Selector {
+    fn parse(text: &str) -> Result<Self, Invalid<'static>> {
+        let (kind, value) = text.split_once(':').ok_or(Invalid::Selector(
+            "Use 'names:<a,b,..>', 'prefix:<p>', 'suffix:<s>' or 'regex:<r>'",
+        ))?;
+        match kind {
+            "names" => Ok(Selector::Names(
+                value.split(',').map(|name| name.trim().into()).collect(),
+            )),
+            "prefix" => Ok(Selector::Prefix(value.into())),
+            "suffix" => Ok(Selector::Suffix(value.into())),
+            "regex" => Regex::new(value)
+                .map(Selector::Regex)
+                .map_err(Invalid::Regex),
+            _ => Err(Invalid::Selector(
+                "Unknown selector. Use 'names', 'prefix', 'suffix' or 'regex'",
+            )),
+        }
+    }
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Selector::Names(names) => names.iter().any(|n| n == name),
+            Selector::Prefix(prefix) => name.starts_with(prefix.as_str()),
+            Selector::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            Selector::Regex(regex) => regex.is_match(name),
+        }
     }
-    fn get_index(&self, value: &str) -> Option<usize> {
-        self.nodes
+    // the indices of the matching nodes, in the order they were first seen
+    fn select(&self, graph: &Graph) -> Result<Vec<usize>, Invalid<'static>> {
+        let selected = graph
+            .nodes
             .iter()
             .enumerate()
-            .find(|(_, n)| n.value == Node::convert(value))
+            .filter(|(_, n)| self.matches(&n.name))
             .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return Err(Invalid::Selector("No node matches the selector"));
+        }
+        Ok(selected)
     }
-    fn add(&mut self, head: &str, left: &str, right: &str) {
-        let left_index = if let Some(i) = self.get_index(left) {
-            i
-        } else {
-            self.nodes.push(Node::new(left));
-            self.nodes.len() - 1
-        };
-        let right_index = if let Some(i) = self.get_index(right) {
-            i
-        } else {
-            self.nodes.push(Node::new(right));
-            self.nodes.len() - 1
-        };
-        match self.get_index(head) {
-            None => {
-                let head_node = Node::with_children(head, left_index, right_index);
-                self.nodes.push(head_node);
-            }
-            Some(head_index) => {
-                self.nodes[head_index].add_left(left_index);
-                self.nodes[head_index].add_right(right_index);
-            }
+}
+
+enum Walk {
+    // the end node and the number of steps it took
+    Arrived(usize, u64),
+    // the step at which the walk entered a loop of (node, instruction index)
+    // states without an end node, and that loop
+    Stuck(usize, Vec<(usize, usize)>),
+}
+
+// the walk is deterministic in (node, instruction index), so coming back to a
+// state means it will go round the same states forever
+fn walk_to_end(graph: &Graph, first: usize, instructions: &[Instruction], end: &Selector) -> Walk {
+    let mut seen: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut path = Vec::new();
+    let mut current = first;
+    let mut counter: u64 = 0;
+    loop {
+        if end.matches(&graph.nodes[current].name) {
+            return Walk::Arrived(current, counter);
         }
+        let i = counter as usize % instructions.len();
+        if let Some(&step) = seen.get(&(current, i)) {
+            return Walk::Stuck(step, path.split_off(step));
+        }
+        seen.insert((current, i), path.len());
+        path.push((current, i));
+        let node = &graph.nodes[current];
+        current = match instructions[i] {
+            Instruction::Left => node.left.expect("No left turn 😵‍💫"),
+            Instruction::Right => node.right.expect("No right turn 😵‍💫"),
+        };
+        counter += 1;
     }
 }
-fn part_one(line_reader: Line) -> Result<(), Error<'static>> {
-    let mut graph = GraphOne::new();
+
+fn part_one(line_reader: Line, start: &Selector, end: &Selector) -> Result<(), Error<'static>> {
+    let mut graph = Graph::new();
     let instructions = read_file(line_reader, &mut graph)?;
-    let mut current = graph.get("AAA").expect("AAA not found");
-    let end = graph.get("ZZZ").expect("ZZZ not found");
-    let mut counter: u64 = 0;
-    'outer: loop {
-        for instruction in &instructions {
-            if current == end {
-                break 'outer;
+    let starts = start.select(&graph).map_err(Error::Inv)?;
+    end.select(&graph).map_err(Error::Inv)?;
+    let mut arrivals = Vec::new();
+    for &first in &starts {
+        match walk_to_end(&graph, first, &instructions, end) {
+            Walk::Arrived(node, counter) => {
+                println!(
+                    "{} -> {}: {}",
+                    graph.nodes[first].name, graph.nodes[node].name, counter
+                );
+                arrivals.push(counter);
             }
-            match instruction {
-                Instruction::Left => {
-                    current = &graph.nodes[current.left.expect("No left turn 😵‍💫")]
-                }
-                Instruction::Right => {
-                    current = &graph.nodes[current.right.expect("No right turn 😵‍💫")]
-                }
+            Walk::Stuck(after, cycle) => {
+                let states = cycle
+                    .iter()
+                    .map(|&(node, i)| format!("{}/{}", graph.nodes[node].name, i))
+                    .collect::<Vec<_>>();
+                println!(
+                    "{}: unreachable, after {} steps it loops through {} states: {} -> {}",
+                    graph.nodes[first].name,
+                    after,
+                    cycle.len(),
+                    states.join(" -> "),
+                    states[0]
+                );
             }
-            counter += 1;
         }
     }
-    println!("total: {}", counter);
+    if let ([_], [counter]) = (&starts[..], &arrivals[..]) {
+        println!("total: {}", counter);
+    }
 
     Ok(())
 }
 
 // #################################################################################
 // part two
-struct GraphTwo {
-    nodes: Vec<Node>,
-    starts: Vec<u16>,
-    ends: Vec<u16>,
+/*
+A ghost walks through states (node, position in the instructions). There are
+finitely many, so after `prefix` steps it enters a cycle of `period` steps and
+never leaves. `hits` are the steps in [0, prefix + period) at which it stands
+on an end node; the ones from `prefix` on repeat every `period` steps.
+`path` is the node it stands on at each of those steps.
+*/
+struct Ghost {
+    start: String,
+    prefix: usize,
+    period: usize,
+    hits: Vec<usize>,
+    path: Vec<usize>,
 }
 
-impl GraphTwo {
-    fn new() -> Self {
-        GraphTwo {
-            nodes: Vec::new(),
-            starts: Vec::new(),
-            ends: Vec::new(),
+impl Ghost {
+    fn walk(graph: &Graph, start: usize, instructions: &[Instruction], end: &Selector) -> Self {
+        let mut seen: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut hits = Vec::new();
+        let mut path = Vec::new();
+        let mut current = start;
+        let mut step = 0;
+        loop {
+            let state = (current, step % instructions.len());
+            if let Some(&first) = seen.get(&state) {
+                return Ghost {
+                    start: graph.nodes[start].name.clone(),
+                    prefix: first,
+                    period: step - first,
+                    hits,
+                    path,
+                };
+            }
+            seen.insert(state, step);
+            path.push(current);
+            let node = &graph.nodes[current];
+            if end.matches(&node.name) {
+                hits.push(step);
+            }
+            current = match instructions[step % instructions.len()] {
+                Instruction::Left => node.left.expect("No left turn 😵‍💫"),
+                Instruction::Right => node.right.expect("No right turn 😵‍💫"),
+            };
+            step += 1;
         }
     }
-    fn push(&mut self, value: &str) {
-        if value.ends_with("A") {
-            self.starts.push(Node::convert(value));
-        }
-        if value.ends_with("Z") {
-            self.ends.push(Node::convert(value));
-        }
+    fn is_hit(&self, step: usize) -> bool {
+        let step = if step < self.prefix + self.period {
+            step
+        } else {
+            self.prefix + (step - self.prefix) % self.period
+        };
+        self.hits.contains(&step)
+    }
+    // the hits inside the cycle as residues modulo the period
+    fn residues(&self) -> Vec<usize> {
+        self.hits
+            .iter()
+            .filter(|&&hit| hit >= self.prefix)
+            .map(|&hit| hit % self.period)
+            .collect()
     }
 }
 
-impl Graph for GraphTwo {
-    fn get(&self, value: &str) -> Option<&Node> {
-        self.nodes.iter().find(|&n| n.value == Node::convert(value))
+// returns (g, x, y) with a * x + b * y = g = gcd(a, b)
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
     }
-    fn get_index(&self, value: &str) -> Option<usize> {
-        self.nodes
-            .iter()
-            .enumerate()
-            .find(|(_, n)| n.value == Node::convert(value))
-            .map(|(i, _)| i)
+}
+
+// solves x = a1 (mod m1) and x = a2 (mod m2) for moduli that need not be
+// coprime, giving x modulo lcm(m1, m2), or None if there is no solution
+fn crt(a1: i128, m1: i128, a2: i128, m2: i128) -> Result<Option<(i128, i128)>, Error<'static>> {
+    let overflow = || Error::Overflow("The cycles of the ghosts line up past i128");
+    let (g, p, _) = extended_gcd(m1, m2);
+    if (a2 - a1) % g != 0 {
+        return Ok(None);
     }
+    let lcm = (m1 / g).checked_mul(m2).ok_or_else(overflow)?;
+    let k = ((a2 - a1) / g)
+        .checked_mul(p)
+        .ok_or_else(overflow)?
+        .rem_euclid(m2 / g);
+    let x = m1
+        .checked_mul(k)
+        .and_then(|x| x.checked_add(a1))
+        .ok_or_else(overflow)?;
+    Ok(Some((x.rem_euclid(lcm), lcm)))
+}
 
-    fn add(&mut self, head: &str, left: &str, right: &str) {
-        let left_index = if let Some(i) = self.get_index(left) {
-            i
-        } else {
-            self.nodes.push(Node::new(left));
-            self.push(left);
-            self.nodes.len() - 1
-        };
-        let right_index = if let Some(i) = self.get_index(right) {
-            i
-        } else {
-            self.nodes.push(Node::new(right));
-            self.push(right);
-            self.nodes.len() - 1
-        };
-        match self.get_index(head) {
-            None => {
-                let head_node = Node::with_children(head, left_index, right_index);
-                self.push(head);
-                self.nodes.push(head_node);
-            }
-            Some(head_index) => {
-                self.nodes[head_index].add_left(left_index);
-                self.nodes[head_index].add_right(right_index);
+// the first step at which every ghost stands on an end node
+fn first_common_step(ghosts: &[Ghost]) -> Result<Option<i128>, Error<'static>> {
+    // before the last ghost enters its cycle the hits do not repeat yet, so
+    // just check every step
+    let settled = ghosts.iter().map(|g| g.prefix).max().unwrap_or(0);
+    if let Some(step) = (0..settled).find(|&step| ghosts.iter().all(|g| g.is_hit(step))) {
+        return Ok(Some(step as i128));
+    }
+    // afterwards every ghost is in its cycle: try each combination of one
+    // residue per ghost and keep the smallest solution from `settled` on
+    let mut combinations: Vec<(i128, i128)> = vec![(0, 1)];
+    for ghost in ghosts {
+        let period = ghost.period as i128;
+        let mut next = Vec::new();
+        for &(a, m) in combinations.iter() {
+            for r in ghost.residues() {
+                next.extend(crt(a, m, r as i128, period)?);
             }
         }
+        combinations = next;
+    }
+    let settled = settled as i128;
+    let mut first = None;
+    for (a, m) in combinations {
+        let step = if a >= settled {
+            a
+        } else {
+            a + (settled - a + m - 1) / m * m
+        };
+        first = Some(first.map_or(step, |first: i128| first.min(step)));
     }
+    Ok(first)
 }
 
-struct List {
-    initials: HashSet<usize>,
-    boundary: usize,
-    period: usize,
-    contents: HashSet<usize>,
-}
+fn part_two(line_reader: Line, start: &Selector, end: &Selector) -> Result<(), Error<'static>> {
+    let mut graph = Graph::new();
+    let instructions = read_file(line_reader, &mut graph)?;
 
-impl List {
-    /*
-    initials is list of Zs position before entering the cycle
-    contents is list of Zs position after entering the cycle
-    boundary is seperating the initial to in cycle
-    period is the period of the cycle. There must be cycle because
-    the instruction is repetitive. The cycle must be multiple
-    of the length of the movements.
-    */
-    fn new(period: usize, boundary: usize) -> Self {
-        List {
-            initials: HashSet::new(),
-            period,
-            boundary,
-            contents: HashSet::new(),
-        }
-    }
-    fn add_initial(&mut self, value: usize) {
-        self.initials.insert(value);
+    let ghosts = start
+        .select(&graph)
+        .map_err(Error::Inv)?
+        .into_iter()
+        .map(|i| Ghost::walk(&graph, i, &instructions, end))
+        .collect::<Vec<_>>();
+    for ghost in &ghosts {
+        let arrival = match ghost.hits.first() {
+            Some(step) => step.to_string(),
+            None => "never".into(),
+        };
+        println!(
+            "{}: arrives {}, prefix {}, period {}, hits {:?}",
+            ghost.start, arrival, ghost.prefix, ghost.period, ghost.hits
+        );
     }
-    fn add_content(&mut self, value: usize) {
-        self.contents.insert(value);
+    match first_common_step(&ghosts)? {
+        Some(step) => println!("found! {}", step),
+        None => println!("the ghosts never meet on end nodes"),
     }
-    fn find(&self, step: usize) -> bool {
-        if step < self.initials.len() {
-            return self.initials.get(&step).is_some();
-        }
-        return self
-            .contents
-            .get(&((step - &self.boundary) % &self.period))
-            .is_some();
-    }
-    fn get(&self) -> (Vec<usize>, Vec<usize>, usize, usize) {
-        // true if in contents
-        let initials = self.initials.iter().cloned().collect::<Vec<_>>();
-        let contents = self.contents.iter().cloned().collect::<Vec<_>>();
-        (
-            initials,
-            contents,
-            self.period.clone(),
-            self.boundary.clone(),
-        )
+    Ok(())
+}
+
+// #################################################################################
+// export
+
+const COLORS: [&str; 8] = [
+    "red",
+    "blue",
+    "darkgreen",
+    "orange",
+    "purple",
+    "brown",
+    "magenta",
+    "cyan",
+];
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn fill(graph: &Graph, node: usize, start: &Selector, end: &Selector) -> &'static str {
+    let name = &graph.nodes[node].name;
+    match (start.matches(name), end.matches(name)) {
+        (true, true) => ", style=filled, fillcolor=gold",
+        (true, false) => ", style=filled, fillcolor=palegreen",
+        (false, true) => ", style=filled, fillcolor=lightcoral",
+        (false, false) => "",
     }
 }
 
-impl std::fmt::Debug for List {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "initials:\t{:?}\ncontents:\t{:?}\nperiod:  \t{}\nboundary:\t{}",
-            self.initials, self.contents, self.period, self.boundary
-        )
+// the colours of the ghosts whose cycle takes `edge`
+fn highlight<T: Hash + Eq>(cycles: &[HashSet<T>], edge: &T) -> String {
+    let colors = cycles
+        .iter()
+        .enumerate()
+        .filter(|(_, cycle)| cycle.contains(edge))
+        .map(|(i, _)| COLORS[i % COLORS.len()])
+        .collect::<Vec<_>>();
+    if colors.is_empty() {
+        String::new()
+    } else {
+        format!(", color=\"{}\", penwidth=2", colors.join(":"))
     }
 }
 
-fn part_two(line_reader: Line) -> Result<(), Error<'static>> {
-    let mut graph = GraphTwo::new();
+// `dot` draws the network itself: start nodes green, end nodes red, and the
+// edges of each ghost's cycle in the ghost's colour. `product` draws the
+// states (node, instruction index) reachable from the start nodes instead,
+// where every state has exactly one way out.
+fn export(
+    line_reader: Line,
+    start: &Selector,
+    end: &Selector,
+    product: bool,
+) -> Result<(), Error<'static>> {
+    let mut graph = Graph::new();
     let instructions = read_file(line_reader, &mut graph)?;
-
-    let nodes = graph
-        .starts
+    let ghosts = start
+        .select(&graph)
+        .map_err(Error::Inv)?
         .into_iter()
-        .map(|s| {
-            graph
-                .nodes
-                .iter()
-                .find(|&n| n.value == s)
-                .expect("not start found?")
-        })
+        .map(|i| Ghost::walk(&graph, i, &instructions, end))
         .collect::<Vec<_>>();
-    let mut counter = 0;
     let len = instructions.len();
-    let mut lists = Vec::new();
-    // creating lists
-    for node in nodes {
-        let mut current = &node.clone();
-        let mut sequences = Vec::new();
-        let mut traces = Vec::new();
-        println!("\n===== {} Position", node.string);
-        'outer: loop {
-            traces.push(current.clone());
-            for instruction in &instructions {
-                match instruction {
-                    Instruction::Left => {
-                        current = &graph.nodes[current.left.expect("No left turn 😵‍💫")];
-                    }
-                    Instruction::Right => {
-                        current = &graph.nodes[current.right.expect("No right turn 😵‍💫")];
-                    }
-                }
-                traces.push(current.clone());
-                counter += 1;
-            }
-            sequences.push(traces.clone());
-            print!("[{}] -> ", traces[0].string);
-            for (i, seq) in traces.iter().enumerate() {
-                if i == instructions.len() || i == 0 {
-                    continue;
-                }
-                if seq.string.ends_with("Z") {
-                    print!("({}) {} -> ", i + counter - instructions.len(), seq.string);
-                }
-            }
-            print!("[{}] {}\n", traces.last().unwrap().string, counter);
-
-            let seq_index = sequences
-                .iter()
-                .enumerate()
-                .find(|(_, p)| p[0] == *current)
-                .map(|(i, _)| i);
-            if let Some(index) = seq_index {
-                traces.clear();
-                counter = 0;
-                let mut list = List::new(len * (sequences.len() - index), index * len);
-                for (i, seq) in sequences.iter().enumerate() {
-                    if i < index {
-                        for (j, s) in seq.iter().enumerate() {
-                            if j == seq.len() - 1 {
-                                continue;
-                            }
-                            if s.string.ends_with("Z") {
-                                list.add_initial(j + i * len);
-                            }
-                        }
-                    } else {
-                        for (j, s) in seq.iter().enumerate() {
-                            if j == seq.len() - 1 {
-                                continue;
-                            }
-                            if s.string.ends_with("Z") {
-                                list.add_content(j + (i - index) * len);
-                            }
-                        }
-                    }
-                }
-                println!("{:?}", list);
-                lists.push(list);
-                break 'outer;
-            }
-            traces.clear();
+    // the (from, to, instruction index) steps of every cycle
+    let cycles = ghosts
+        .iter()
+        .map(|ghost| {
+            (ghost.prefix..ghost.prefix + ghost.period)
+                .map(|step| {
+                    let next = ghost
+                        .path
+                        .get(step + 1)
+                        .unwrap_or(&ghost.path[ghost.prefix]);
+                    (ghost.path[step], *next, step % len)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut out = String::new();
+    if product {
+        out.push_str("digraph product {\n");
+        let mut states = ghosts
+            .iter()
+            .flat_map(|ghost| {
+                ghost
+                    .path
+                    .iter()
+                    .enumerate()
+                    .map(|(step, &node)| (node, step % len))
+            })
+            .collect::<Vec<_>>();
+        states.sort();
+        states.dedup();
+        for &(node, i) in &states {
+            let name = format!("{}/{}", graph.nodes[node].name, i);
+            out.push_str(&format!(
+                "    {} [label={}{}];\n",
+                quote(&name),
+                quote(&name),
+                fill(&graph, node, start, end)
+            ));
         }
-    }
-
-    // executing...
-    let (initials, contents, period, boundary) = lists[0].get();
-    let mut complete = true;
-    let mut loc = 0;
-    // if we find all zs in the initials moves
-    for t in &initials {
-        loc = t.clone();
-        for list in &lists {
-            if !list.find(loc) {
-                complete = false;
-                break;
+        // a state already fixes where it goes next
+        let cycles = cycles
+            .iter()
+            .map(|cycle| cycle.iter().map(|&(from, _, i)| (from, i)).collect())
+            .collect::<Vec<HashSet<_>>>();
+        for &(node, i) in &states {
+            let next = match instructions[i] {
+                Instruction::Left => graph.nodes[node].left,
+                Instruction::Right => graph.nodes[node].right,
             }
+            .expect("No turn 😵‍💫");
+            let label = match instructions[i] {
+                Instruction::Left => "L",
+                Instruction::Right => "R",
+            };
+            out.push_str(&format!(
+                "    {} -> {} [label={}{}];\n",
+                quote(&format!("{}/{}", graph.nodes[node].name, i)),
+                quote(&format!("{}/{}", graph.nodes[next].name, (i + 1) % len)),
+                label,
+                highlight(&cycles, &(node, i))
+            ));
         }
-    }
-    if complete && initials.len() > 0 {
-        println!("found! {}", loc);
-        return Ok(());
-    }
-    // most likely, the zs in the cycle moves
-    let mut cycle: usize = 0;
-    println!();
-    'outer: loop {
-        let mut complete = true;
-        'inner: for t in &contents {
-            loc = t.clone() + cycle * period + boundary;
-            for list in &lists {
-                if !list.find(loc) {
-                    complete = false;
-                    continue 'inner;
-                }
-            }
-            if complete {
-                break 'outer;
-            }
+    } else {
+        out.push_str("digraph network {\n");
+        for (i, node) in graph.nodes.iter().enumerate() {
+            out.push_str(&format!(
+                "    {} [label={}{}];\n",
+                i,
+                quote(&node.name),
+                fill(&graph, i, start, end)
+            ));
         }
-        cycle += 1;
-        if cycle % 100_000 == 0 {
-            print!("\rCounter: {}, cycle: {}", loc, cycle);
+        // which way each cycle step went, to tell the L and R edges apart
+        let cycles = cycles
+            .iter()
+            .map(|cycle| {
+                cycle
+                    .iter()
+                    .map(|&(from, to, i)| (from, to, instructions[i].clone()))
+                    .collect()
+            })
+            .collect::<Vec<HashSet<_>>>();
+        for (i, node) in graph.nodes.iter().enumerate() {
+            for (child, instruction, label) in [
+                (node.left, Instruction::Left, "L"),
+                (node.right, Instruction::Right, "R"),
+            ] {
+                let child = child.expect("No turn 😵‍💫");
+                out.push_str(&format!(
+                    "    {} -> {} [label={}{}];\n",
+                    i,
+                    child,
+                    label,
+                    highlight(&cycles, &(i, child, instruction))
+                ));
+            }
         }
     }
-    println!("\nfound! {}", loc);
+    out.push_str("}\n");
+    print!("{}", out);
     Ok(())
 }
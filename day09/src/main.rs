@@ -1,13 +1,22 @@
 use std::env;
+use std::fmt::{Debug, Display};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::path::PathBuf;
+use std::str::FromStr;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
     Io(io::Error),
     InvalidArg(&'a str),
+    // the line that could not be parsed
+    Parse(usize),
+    // the line whose arithmetic overflowed
+    Overflow(usize),
+    // the line whose history never reaches a constant row
+    NotPolynomial(usize),
 }
 
 fn main() -> Result<(), Error<'static>> {
@@ -15,34 +24,110 @@ fn main() -> Result<(), Error<'static>> {
     let args: Vec<String> = env::args().collect();
     // let file_path = root_dir.join("src").join("sample-1.txt");
     let file_path = root_dir.join("src").join("input.txt");
-    let mut line_reader = Line::new(file_path).map_err(Error::Io)?;
+    let line_reader = Line::new(file_path).map_err(Error::Io)?;
 
-    let func = match args.get(1) {
-        Some(arg) => match arg.as_str() {
-            "1" => part_one,
-            "2" => part_two,
-            _ => {
-                return Err(Error::InvalidArg("Invalid argument. Use '1' or '2'"));
-            }
-        },
-        None => {
-            return Err(Error::NoArg("Missing argument. Use '1' or '2'"));
-        }
+    // 'extend <forward> [backward]' predicts that many readings on each side,
+    // 'fit' prints the polynomial behind each history, '--table' also prints
+    // the whole difference table, '--i128' swaps the default i64 arithmetic
+    // for i128, and '--strict' turns the warning about histories that are not
+    // polynomials into an error
+    let flags = ["--table", "--i128", "--strict"];
+    let options = Options {
+        table: args.iter().any(|arg| arg == "--table"),
+        strict: args.iter().any(|arg| arg == "--strict"),
+    };
+    let wide = args.iter().any(|arg| arg == "--i128");
+    let args = args
+        .iter()
+        .filter(|&arg| !flags.contains(&arg.as_str()))
+        .collect::<Vec<_>>();
+    let mode = match args.get(1).map(|arg| arg.as_str()) {
+        Some("1") => Mode::One,
+        Some("2") => Mode::Two,
+        Some("extend") => Mode::Extend(parse_count(args.get(2))?, parse_count(args.get(3))?),
+        Some("fit") => Mode::Fit,
+        Some(_) => return Err(Error::InvalidArg(USAGE)),
+        None => return Err(Error::NoArg(USAGE)),
     };
+    if wide {
+        run::<i128>(line_reader, &mode, &options)
+    } else {
+        run::<i64>(line_reader, &mode, &options)
+    }
+}
+
+enum Mode {
+    One,
+    Two,
+    // readings to predict after and before each history
+    Extend(usize, usize),
+    Fit,
+}
+
+struct Options {
+    table: bool,
+    strict: bool,
+}
 
-    let mut total = 0;
+fn run<T: Number>(
+    mut line_reader: Line,
+    mode: &Mode,
+    options: &Options,
+) -> Result<(), Error<'static>> {
+    let (forward, backward) = match mode {
+        Mode::One => (1, 0),
+        Mode::Two => (0, 1),
+        Mode::Extend(forward, backward) => (*forward, *backward),
+        Mode::Fit => (0, 0),
+    };
+    let mut total = T::zero();
+    let mut line_number = 0;
     loop {
         match line_reader.read() {
             Ok(Some(line)) => {
+                line_number += 1;
                 let sequence = line
                     .split_ascii_whitespace()
-                    .map(|l| l.parse::<i32>().unwrap())
-                    .collect::<Vec<_>>();
-                let prediction = func(&sequence);
-                // println!("{:?}", sequence);
+                    .map(|l| l.parse::<T>().map_err(|_| Error::Parse(line_number)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if sequence.is_empty() {
+                    continue;
+                }
+                let overflow = |_| Error::Overflow(line_number);
+                let mut table = Table::new(&sequence).map_err(overflow)?;
+                if !table.is_polynomial() {
+                    if options.strict {
+                        return Err(Error::NotPolynomial(line_number));
+                    }
+                    eprintln!(
+                        "warning: line {} does not reach a constant row within its {} readings",
+                        line_number,
+                        sequence.len()
+                    );
+                }
+                if let Mode::Fit = mode {
+                    let polynomial = fit(&table).ok_or(Error::Overflow(line_number))?;
+                    println!("p(x) = {}", show_polynomial(&polynomial));
+                    continue;
+                }
+                table.extend(forward, backward).map_err(overflow)?;
+                if options.table {
+                    for row in &table.rows {
+                        println!("{:?}", row);
+                    }
+                }
+                let prediction = match mode {
+                    Mode::One => table.rows[0][table.rows[0].len() - 1],
+                    Mode::Two => table.rows[0][0],
+                    Mode::Extend(_, _) | Mode::Fit => {
+                        println!("{:?}", table.rows[0]);
+                        continue;
+                    }
+                };
                 println!("prediction = {}", prediction);
-                println!();
-                total += prediction;
+                total = total
+                    .checked_add(prediction)
+                    .ok_or(Error::Overflow(line_number))?;
             }
             Ok(None) => {
                 // End of file reached
@@ -54,10 +139,23 @@ fn main() -> Result<(), Error<'static>> {
             }
         }
     }
-    println!("Total = {}", total);
+    if let Mode::One | Mode::Two = mode {
+        println!("Total = {}", total);
+    }
 
     Ok(())
 }
+
+const USAGE: &str = "Use '1', '2', 'extend <forward> [backward]' or 'fit'";
+
+fn parse_count(arg: Option<&&String>) -> Result<usize, Error<'static>> {
+    match arg {
+        None => Ok(0),
+        Some(arg) => arg
+            .parse()
+            .map_err(|_| Error::InvalidArg("The number of steps must be a number")),
+    }
+}
 // #################################################################################
 // #################################################################################
 // #################################################################################
@@ -68,7 +166,7 @@ struct Line {
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -89,57 +187,180 @@ impl Line {
 }
 // #################################################################################
 
-fn part_one(sequence: &Vec<i32>) -> i32 {
-    let mut sequences = vec![sequence.clone()];
-    let mut next_seq = Vec::new();
-    println!("{:?}", sequences[0]);
-    for _ in 1..sequence.len() {
-        let current_seq = sequences.last().unwrap().iter().rev().collect::<Vec<_>>();
-        for seq in current_seq.windows(2) {
-            next_seq.push(seq[0] - seq[1])
+// the integer types the table can be computed in; every operation is
+// checked so an overflow is reported instead of wrapping around
+trait Number: Copy + Debug + Display + FromStr + PartialEq {
+    fn zero() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn to_i128(self) -> i128;
+}
+
+macro_rules! number {
+    ($($t:ty),*) => {
+        $(
+            impl Number for $t {
+                fn zero() -> Self {
+                    0
+                }
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+            }
+        )*
+    };
+}
+
+number!(i64, i128);
+
+#[derive(Debug)]
+struct Overflow;
+
+// the history on top and each row of differences below it, down to the
+// first row whose values are all the same
+struct Table<T> {
+    rows: Vec<Vec<T>>,
+}
+
+impl<T: Number> Table<T> {
+    fn new(sequence: &[T]) -> Result<Self, Overflow> {
+        let mut rows = vec![sequence.to_vec()];
+        loop {
+            let last = rows.last().unwrap();
+            if last.len() < 2 || last.iter().all(|&s| s == last[0]) {
+                break;
+            }
+            let next = last
+                .windows(2)
+                .map(|w| w[1].checked_sub(w[0]).ok_or(Overflow))
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.push(next);
+        }
+        Ok(Table { rows })
+    }
+    // the constant row has to hold at least two equal values, otherwise the
+    // differences simply ran out and nothing says the history is a polynomial
+    fn is_polynomial(&self) -> bool {
+        self.rows[self.rows.len() - 1].len() >= 2
+    }
+    // adds `forward` values after and `backward` values before every row,
+    // starting from the constant bottom row and working up
+    fn extend(&mut self, forward: usize, backward: usize) -> Result<(), Overflow> {
+        for _ in 0..forward {
+            let mut below = T::zero();
+            for row in self.rows.iter_mut().rev() {
+                let next = row[row.len() - 1].checked_add(below).ok_or(Overflow)?;
+                row.push(next);
+                below = next;
+            }
+        }
+        for _ in 0..backward {
+            let mut below = T::zero();
+            for row in self.rows.iter_mut().rev() {
+                let previous = row[0].checked_sub(below).ok_or(Overflow)?;
+                row.insert(0, previous);
+                below = previous;
+            }
+        }
+        Ok(())
+    }
+}
+
+// #################################################################################
+// fit
+
+// the value at x = 0 of row k is the k-th forward difference, so Newton's
+// formula gives p(x) = sum over k of rows[k][0] * x (x - 1) ... (x - k + 1) / k!.
+// Every term is brought over the common denominator d! (d the degree) and the
+// result is returned as (numerator, denominator) pairs from x^0 up, reduced.
+// None if the numbers do not fit in an i128.
+fn fit<T: Number>(table: &Table<T>) -> Option<Vec<(i128, i128)>> {
+    let degree = table.rows.len() - 1;
+    let factorials = (0..=degree as i128).try_fold(vec![1i128], |mut acc, k| {
+        if k > 0 {
+            acc.push(acc[acc.len() - 1].checked_mul(k)?);
+        }
+        Some(acc)
+    })?;
+    let denominator = factorials[degree];
+    let mut numerators = vec![0i128; degree + 1];
+    // x (x - 1) ... (x - k + 1), lowest power first
+    let mut falling = vec![1i128];
+    for (k, row) in table.rows.iter().enumerate() {
+        if k > 0 {
+            let shift = (k - 1) as i128;
+            let mut next = vec![0i128; falling.len() + 1];
+            for (j, &c) in falling.iter().enumerate() {
+                next[j + 1] = next[j + 1].checked_add(c)?;
+                next[j] = next[j].checked_sub(c.checked_mul(shift)?)?;
+            }
+            falling = next;
         }
-        next_seq.reverse();
-        println!("{:?}", next_seq);
-        if next_seq.iter().all(|&s| s == next_seq[0]) {
-            sequences.push(std::mem::take(&mut next_seq));
-            break;
+        let scale = row[0].to_i128().checked_mul(denominator / factorials[k])?;
+        for (j, &c) in falling.iter().enumerate() {
+            numerators[j] = numerators[j].checked_add(c.checked_mul(scale)?)?;
         }
-        sequences.push(std::mem::take(&mut next_seq));
     }
-    let res = sequences
-        .iter()
-        .map(|s| s.last().unwrap())
-        .cloned()
-        .reduce(|acc, e| acc + e)
-        .unwrap();
+    Some(
+        numerators
+            .into_iter()
+            .map(|n| {
+                let g = gcd(n, denominator);
+                (n / g, denominator / g)
+            })
+            .collect(),
+    )
+}
 
-    res
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
 }
 
-// part two
-fn part_two(sequence: &Vec<i32>) -> i32 {
-    let mut sequences = vec![sequence.clone()];
-    let mut next_seq = Vec::new();
-    for _ in 1..sequence.len() {
-        let current_seq = sequences.last().unwrap().iter().rev().collect::<Vec<_>>();
-        for seq in current_seq.windows(2) {
-            next_seq.push(seq[0] - seq[1])
+fn show_polynomial(coefficients: &[(i128, i128)]) -> String {
+    let mut out = String::new();
+    for (power, &(n, d)) in coefficients.iter().enumerate().rev() {
+        if n == 0 {
+            continue;
         }
-        next_seq.reverse();
-        if next_seq.iter().all(|&s| s == next_seq[0]) {
-            sequences.push(std::mem::take(&mut next_seq));
-            break;
+        let sign = if n < 0 { "-" } else { "+" };
+        if out.is_empty() {
+            if n < 0 {
+                out.push('-');
+            }
+        } else {
+            out.push_str(&format!(" {} ", sign));
+        }
+        let magnitude = if d == 1 {
+            n.abs().to_string()
+        } else {
+            format!("{}/{}", n.abs(), d)
+        };
+        match power {
+            0 => out.push_str(&magnitude),
+            _ => {
+                if magnitude != "1" {
+                    out.push_str(&magnitude);
+                    out.push(' ');
+                }
+                out.push('x');
+                if power > 1 {
+                    out.push_str(&format!("^{}", power));
+                }
+            }
         }
-        sequences.push(std::mem::take(&mut next_seq));
-    }
-    let mut curr = 0;
-    for seq in sequences.iter_mut().rev() {
-        let first = seq[0];
-        curr = first - curr;
-        seq.insert(0, curr);
     }
-    for seq in &sequences {
-        println!("{:?}", seq);
+    if out.is_empty() {
+        out.push('0');
     }
-    sequences[0][0]
+    out
 }
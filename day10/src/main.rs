@@ -1,13 +1,42 @@
 use std::env;
-use std::fs::File;
+use std::fmt::Write;
+use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader};
 use std::path::PathBuf;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error<'a> {
     NoArg(&'a str),
     Io(io::Error),
     InvalidArg(&'a str),
+    // the map holds no 'S'
+    NoStart,
+    // 'S' must be connected to exactly two neighbours
+    Start((usize, usize), Vec<Direction>),
+    // the scanner and the shoelace formula disagree (scanner, shoelace)
+    Mismatch(usize, usize),
+    // the loop through 'S' does not close
+    Broken(Broken),
+}
+
+// the pipe at `at` leads `towards` somewhere it cannot continue
+#[allow(dead_code)]
+#[derive(Debug)]
+struct Broken {
+    at: (usize, usize),
+    pipe: Kind,
+    towards: Direction,
+    reason: Reason,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+enum Reason {
+    OutOfBounds,
+    Ground,
+    // the neighbour is a pipe of this kind that does not connect back
+    Disconnected(Kind),
 }
 
 fn main() -> Result<(), Error<'static>> {
@@ -15,30 +44,105 @@ fn main() -> Result<(), Error<'static>> {
     let args: Vec<String> = env::args().collect();
     // let file_path = root_dir.join("src").join("sample-7.txt");
     let file_path = root_dir.join("src").join("input.txt");
-    let animal_kind = Kind::NorthWest; // change based on the input
-    let moves = Direction::West; // change based on the input
-
     let line_reader = Line::new(file_path).map_err(Error::Io)?;
 
-    let func = match args.get(1) {
+    let part = match args.get(1) {
         Some(arg) => match arg.as_str() {
-            "1" => part_one,
-            "2" => part_two,
+            "1" => Part::One,
+            "2" => Part::Two,
+            "loops" => Part::Loops,
             _ => {
-                return Err(Error::InvalidArg("Invalid argument. Use '1' or '2'"));
+                return Err(Error::InvalidArg(
+                    "Invalid argument. Use '1', '2' or 'loops'",
+                ));
             }
         },
         None => {
-            return Err(Error::NoArg("Missing argument. Use '1' or '2'"));
+            return Err(Error::NoArg("Missing argument. Use '1', '2' or 'loops'"));
+        }
+    };
+    // how part two counts the enclosed tiles: the row scanner (default), the
+    // shoelace formula with Pick's theorem, or both checked against each other
+    let area = match args.iter().find_map(|arg| arg.strip_prefix("--area=")) {
+        None | Some("scan") => Area::Scan,
+        Some("shoelace") => Area::Shoelace,
+        Some("both") => Area::Both,
+        Some(_) => {
+            return Err(Error::InvalidArg(
+                "Invalid area. Use '--area=scan', '--area=shoelace' or '--area=both'",
+            ));
         }
     };
 
-    let map = read_file(line_reader, animal_kind)?;
+    // '--render=ascii' or '--render=unicode' draws the map once solved, and
+    // '--svg=<file>' writes it as an SVG picture
+    let render = match args.iter().find_map(|arg| arg.strip_prefix("--render=")) {
+        None => None,
+        Some("ascii") => Some(Style::Ascii),
+        Some("unicode") => Some(Style::Unicode),
+        Some(_) => {
+            return Err(Error::InvalidArg(
+                "Invalid render. Use '--render=ascii' or '--render=unicode'",
+            ));
+        }
+    };
+    let svg = args.iter().find_map(|arg| arg.strip_prefix("--svg="));
 
-    func(map, moves);
+    let mut map = read_file(line_reader)?;
+
+    match part {
+        Part::One => {
+            let moves = infer_start(&mut map)?;
+            part_one(&mut map, moves)?;
+        }
+        Part::Loops => {
+            // the loops do not need an 'S', but one that is there must fit
+            match infer_start(&mut map) {
+                Ok(_) | Err(Error::NoStart) => (),
+                Err(e) => return Err(e),
+            }
+            loops(&mut map);
+        }
+        Part::Two => {
+            let moves = infer_start(&mut map)?;
+            part_two(&mut map, moves, &area)?;
+            // the shoelace formula only counts, the scanner marks the tiles
+            if let Area::Shoelace = area {
+                if render.is_some() || svg.is_some() {
+                    scan(&mut map);
+                }
+            }
+        }
+    }
+    // only part two knows which tiles are inside
+    let classified = matches!(part, Part::Two);
+    if let Some(style) = render {
+        print!("\n{}", draw(&map, &style, classified));
+    }
+    if let Some(path) = svg {
+        fs::write(root_dir.join(path), to_svg(&map, classified)).map_err(Error::Io)?;
+    }
 
     Ok(())
 }
+
+enum Part {
+    One,
+    Two,
+    // every closed loop of the map, not only the one through 'S'
+    Loops,
+}
+
+enum Area {
+    Scan,
+    Shoelace,
+    Both,
+}
+
+enum Style {
+    Ascii,
+    Unicode,
+}
 // #################################################################################
 // #################################################################################
 // #################################################################################
@@ -49,7 +153,7 @@ struct Line {
 
 impl Line {
     fn new(file_path: PathBuf) -> io::Result<Self> {
-        let file = File::open(file_path).expect(&*format!("File not found"));
+        let file = File::open(file_path).expect("File not found");
         let reader = BufReader::new(file);
         Ok(Line { reader })
     }
@@ -96,6 +200,17 @@ struct Pipe {
     dir: (Direction, Direction),
 }
 
+fn kind_to_box(kind: &Kind) -> char {
+    match kind {
+        Kind::NorthSouth => '│',
+        Kind::WestEast => '─',
+        Kind::NorthEast => '└',
+        Kind::NorthWest => '┘',
+        Kind::SouthWest => '┐',
+        Kind::SouthEast => '┌',
+    }
+}
+
 fn kind_to_dir(kind: &Kind) -> (Direction, Direction) {
     match kind {
         Kind::NorthEast => (Direction::North, Direction::East),
@@ -125,17 +240,12 @@ impl Pipe {
             kind,
         }
     }
-    fn moves(&mut self, from: Direction) -> Direction {
+    // None if the pipe has no opening towards where we come from
+    fn moves(&self, from: &Direction) -> Option<Direction> {
         match from {
-            dir if opposite(&dir) == self.dir.0 => {
-                self.in_loop = true;
-                return self.dir.1.clone();
-            }
-            dir if opposite(&dir) == self.dir.1 => {
-                self.in_loop = true;
-                return self.dir.0.clone();
-            }
-            _ => unreachable!("impossible direction"),
+            dir if opposite(dir) == self.dir.0 => Some(self.dir.1.clone()),
+            dir if opposite(dir) == self.dir.1 => Some(self.dir.0.clone()),
+            _ => None,
         }
     }
 }
@@ -174,7 +284,6 @@ enum Tile {
     Pipe(Pipe),
 }
 
-
 impl std::fmt::Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
@@ -191,10 +300,11 @@ impl std::fmt::Display for Tile {
     }
 }
 
-fn read_tile(ch: char, animal_kind: Kind) -> Tile {
+fn read_tile(ch: char) -> Tile {
     match ch {
         '.' => Tile::Nil(Loc::Outside), // by default is outside
-        'S' => Tile::Pipe(Pipe::new(animal_kind, true)),
+        // the real kind is only known once the neighbours are read, see `infer_start`
+        'S' => Tile::Pipe(Pipe::new(Kind::NorthSouth, true)),
         '|' => Tile::Pipe(Pipe::new(Kind::NorthSouth, false)),
         '-' => Tile::Pipe(Pipe::new(Kind::WestEast, false)),
         'L' => Tile::Pipe(Pipe::new(Kind::NorthEast, false)),
@@ -210,38 +320,37 @@ struct Map {
     tiles: Vec<Vec<Tile>>,
     x_dim: usize,
     y_dim: usize,
-    start: (usize, usize),
+    start: Option<(usize, usize)>,
 }
 
 impl Map {
-    fn find(&mut self, x: usize, y: usize) -> Tile {
-        self.tiles[y][x].clone()
-    }
     fn update(&mut self, x: usize, y: usize, tile: &Tile) {
         self.tiles[y][x] = tile.clone();
     }
+    fn mark(&mut self, path: &[(usize, usize)]) {
+        for &(x, y) in path {
+            if let Tile::Pipe(pipe) = &mut self.tiles[y][x] {
+                pipe.in_loop = true;
+            }
+        }
+    }
 }
 
-fn read_file(mut line_reader: Line, animal_kind: Kind) -> Result<Map, Error<'static>> {
+fn read_file(mut line_reader: Line) -> Result<Map, Error<'static>> {
     let mut tiles = Vec::new();
     let mut current_tiles = Vec::new();
-    let mut start = (0, 0);
+    let mut start = None;
     let mut y: usize = 0;
     loop {
         match line_reader.read() {
             Ok(Some(line)) => {
                 for (x, ch) in line.chars().enumerate() {
-                    let tile = read_tile(ch, animal_kind.clone());
+                    let tile = read_tile(ch);
                     if let Tile::Pipe(pipe) = &tile {
                         if pipe.animal {
-                            start = (x, y);
+                            start = Some((x, y));
                         }
                     }
-                    if tile == Tile::Nil(Loc::Outside) {
-                        print!(".");
-                    } else {
-                        print!("{}", tile);
-                    }
                     current_tiles.push(tile);
                 }
                 y += 1;
@@ -256,9 +365,7 @@ fn read_file(mut line_reader: Line, animal_kind: Kind) -> Result<Map, Error<'sta
                 return Err(Error::Io(e));
             }
         }
-        println!();
     }
-    println!();
     let x_dim = tiles[0].len();
     let y_dim = tiles.len();
     Ok(Map {
@@ -269,85 +376,325 @@ fn read_file(mut line_reader: Line, animal_kind: Kind) -> Result<Map, Error<'sta
     })
 }
 
-fn next_move(
-    moves: &Direction,
-    current: (usize, usize),
-    x_max: usize,
-    y_max: usize,
-) -> (usize, usize) {
-    let mut x_pos: usize = current.0;
-    let mut y_pos = current.1;
-    match moves {
-        Direction::East => x_pos += 1,
-        Direction::West => x_pos -= 1,
-        Direction::North => y_pos -= 1,
-        Direction::South => y_pos += 1,
+fn dir_to_kind(dir: (&Direction, &Direction)) -> Option<Kind> {
+    [
+        Kind::NorthSouth,
+        Kind::WestEast,
+        Kind::NorthEast,
+        Kind::NorthWest,
+        Kind::SouthWest,
+        Kind::SouthEast,
+    ]
+    .into_iter()
+    .find(|kind| {
+        let (a, b) = kind_to_dir(kind);
+        (&a, &b) == dir || (&b, &a) == dir
+    })
+}
+
+// a neighbour is connected to 'S' when it is a pipe pointing back at it.
+// 'S' becomes the pipe joining its two connected neighbours, and the walk
+// leaves through the first of them
+fn infer_start(map: &mut Map) -> Result<Direction, Error<'static>> {
+    let (x, y) = map.start.ok_or(Error::NoStart)?;
+    let mut connected = Vec::new();
+    for dir in [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ] {
+        let neighbour = match dir {
+            Direction::North if y > 0 => (x, y - 1),
+            Direction::West if x > 0 => (x - 1, y),
+            Direction::South if y + 1 < map.y_dim => (x, y + 1),
+            Direction::East if x + 1 < map.tiles[y].len() => (x + 1, y),
+            _ => continue,
+        };
+        if let Tile::Pipe(pipe) = &map.tiles[neighbour.1][neighbour.0] {
+            let back = opposite(&dir);
+            if pipe.dir.0 == back || pipe.dir.1 == back {
+                connected.push(dir);
+            }
+        }
     }
-    if x_pos > x_max {
-        panic!("Out of x bound T_T");
+    if connected.len() != 2 {
+        return Err(Error::Start((x, y), connected));
     }
-    if y_pos > y_max {
-        panic!("Out of y bound T_T");
+    // any two distinct directions make a pipe
+    let kind = dir_to_kind((&connected[0], &connected[1])).unwrap();
+    let pipe = Pipe::new(kind, true);
+    let moves = pipe.dir.0.clone();
+    map.update(x, y, &Tile::Pipe(pipe));
+    Ok(moves)
+}
+
+// None when the move leaves the map
+fn next_move(moves: &Direction, current: (usize, usize), map: &Map) -> Option<(usize, usize)> {
+    let (x, y) = current;
+    let next = match moves {
+        Direction::East => (x + 1, y),
+        Direction::West => (x.checked_sub(1)?, y),
+        Direction::North => (x, y.checked_sub(1)?),
+        Direction::South => (x, y + 1),
+    };
+    if next.1 >= map.y_dim || next.0 >= map.tiles[next.1].len() {
+        return None;
     }
-    (x_pos, y_pos)
+    Some(next)
 }
 
-fn part_one(mut map: Map, mut moves: Direction) {
-    let mut current = map.start.clone();
-    let x_max = map.x_dim;
-    let y_max = map.y_dim;
-    let mut traces = Vec::new();
-    let mut counter: usize = 0;
+// follows the pipes from `start`, leaving it `towards`, until it is entered
+// again. `path` receives every tile walked through, also when the walk breaks
+fn walk(
+    map: &Map,
+    start: (usize, usize),
+    mut towards: Direction,
+    path: &mut Vec<(usize, usize)>,
+) -> Result<(), Broken> {
+    let mut current = start;
     loop {
-        let mut tile = map.find(current.0, current.1);
-        moves = match &mut tile {
-            Tile::Nil(_) => panic!("Cannot step on dot"),
-            Tile::Pipe(ref mut pipe) => {
-                if pipe.animal {
-                    if traces.len() != 0 {
-                        break;
-                    }
-                    moves
-                } else {
-                    pipe.moves(moves)
+        path.push(current);
+        let pipe = match &map.tiles[current.1][current.0] {
+            Tile::Pipe(pipe) => pipe.kind.clone(),
+            Tile::Nil(_) => unreachable!("walks only go through pipes"),
+        };
+        let broken = |towards: Direction, reason| Broken {
+            at: current,
+            pipe,
+            towards,
+            reason,
+        };
+        let next = match next_move(&towards, current, map) {
+            Some(next) => next,
+            None => return Err(broken(towards, Reason::OutOfBounds)),
+        };
+        let moves = match &map.tiles[next.1][next.0] {
+            Tile::Nil(_) => return Err(broken(towards, Reason::Ground)),
+            Tile::Pipe(next_pipe) => match next_pipe.moves(&towards) {
+                Some(moves) => moves,
+                None => {
+                    let reason = Reason::Disconnected(next_pipe.kind.clone());
+                    return Err(broken(towards, reason));
                 }
-            }
+            },
         };
-        traces.push(tile);
-        let next_moves = next_move(&moves, current, x_max, y_max);
-        counter += 1;
-        current = next_moves;
+        if next == start {
+            return Ok(());
+        }
+        current = next;
+        towards = moves;
     }
-    println!("\n{}", counter / 2);
 }
 
-fn part_two(mut map: Map, mut moves: Direction) {
-    let mut current = map.start.clone();
-    let x_max = map.x_dim;
-    let y_max = map.y_dim;
-    // let mut traces = Vec::new();
-    let mut running = false;
-    loop {
-        let mut tile = map.find(current.0, current.1);
-        moves = match &mut tile {
-            Tile::Nil(_) => panic!("Cannot step on dot"),
-            Tile::Pipe(ref mut pipe) => {
-                if pipe.animal {
-                    if running {
-                        break;
+// #################################################################################
+// rendering
+
+const LOOP: &str = "\x1b[32m";
+const START: &str = "\x1b[1;31m";
+const INSIDE: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+// the loop is coloured in the unicode style. Before classification every
+// ground tile is shown as '.', afterwards as 'I' or 'O'
+fn draw(map: &Map, style: &Style, classified: bool) -> String {
+    let mut out = String::new();
+    for tiles in map.tiles.iter() {
+        for tile in tiles {
+            let ch = match tile {
+                Tile::Nil(_) if !classified => '.',
+                Tile::Nil(Loc::Inside) => 'I',
+                Tile::Nil(Loc::Outside) => 'O',
+                Tile::Pipe(pipe) => match style {
+                    Style::Ascii => tile.to_string().chars().next().unwrap(),
+                    Style::Unicode => kind_to_box(&pipe.kind),
+                },
+            };
+            let colour = match (style, tile) {
+                (Style::Ascii, _) => "",
+                (_, Tile::Pipe(pipe)) if pipe.animal => START,
+                (_, Tile::Pipe(pipe)) if pipe.in_loop => LOOP,
+                (_, Tile::Nil(Loc::Inside)) if classified => INSIDE,
+                _ => DIM,
+            };
+            if colour.is_empty() {
+                out.push(ch);
+            } else {
+                let _ = write!(out, "{}{}{}", colour, ch, RESET);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+const CELL: usize = 10;
+
+// every pipe is drawn as two strokes from the centre of its cell to the
+// middle of the edges it connects, the loop in colour and the rest in grey
+fn to_svg(map: &Map, classified: bool) -> String {
+    let mut out = String::new();
+    let width = map.x_dim * CELL;
+    let height = map.y_dim * CELL;
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    );
+    let _ = writeln!(
+        out,
+        "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>",
+        width, height
+    );
+    let half = CELL / 2;
+    for (y, tiles) in map.tiles.iter().enumerate() {
+        for (x, tile) in tiles.iter().enumerate() {
+            let (left, top) = (x * CELL, y * CELL);
+            match tile {
+                Tile::Nil(Loc::Inside) if classified => {
+                    let _ = writeln!(
+                        out,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"gold\"/>",
+                        left, top, CELL, CELL
+                    );
+                }
+                Tile::Nil(_) => (),
+                Tile::Pipe(pipe) => {
+                    let (cx, cy) = (left + half, top + half);
+                    let mut d = String::new();
+                    for dir in [&pipe.dir.0, &pipe.dir.1] {
+                        let (ex, ey) = match dir {
+                            Direction::North => (cx, top),
+                            Direction::South => (cx, top + CELL),
+                            Direction::West => (left, cy),
+                            Direction::East => (left + CELL, cy),
+                        };
+                        let _ = write!(d, "M{} {}L{} {}", cx, cy, ex, ey);
+                    }
+                    let (stroke, width) = if pipe.in_loop {
+                        ("green", 3)
+                    } else {
+                        ("lightgrey", 1)
+                    };
+                    let _ = writeln!(
+                        out,
+                        "<path d=\"{}\" stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\" fill=\"none\"/>",
+                        d, stroke, width
+                    );
+                    if pipe.animal {
+                        let _ = writeln!(
+                            out,
+                            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"red\"/>",
+                            cx,
+                            cy,
+                            CELL / 3
+                        );
                     }
-                    running = true;
-                    moves
-                } else {
-                    let u = pipe.moves(moves);
-                    u
                 }
             }
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+// #################################################################################
+
+fn part_one(map: &mut Map, moves: Direction) -> Result<(), Error<'static>> {
+    let mut path = Vec::new();
+    walk(map, map.start.unwrap(), moves, &mut path).map_err(Error::Broken)?;
+    map.mark(&path);
+    println!("\n{}", path.len() / 2);
+    Ok(())
+}
+
+fn part_two(map: &mut Map, moves: Direction, area: &Area) -> Result<(), Error<'static>> {
+    let mut path = Vec::new();
+    walk(map, map.start.unwrap(), moves, &mut path).map_err(Error::Broken)?;
+    map.mark(&path);
+    let enclosed = match area {
+        Area::Scan => scan(map),
+        Area::Shoelace => shoelace(&path),
+        Area::Both => {
+            let scanned = scan(map);
+            let picked = shoelace(&path);
+            if scanned != picked {
+                return Err(Error::Mismatch(scanned, picked));
+            }
+            scanned
+        }
+    };
+    println!("\n{}", enclosed);
+    Ok(())
+}
+
+// tries a walk from every pipe not seen yet. A walk that breaks cannot pass
+// through a loop, so it is followed both ways and the whole open chain of
+// connected pipes is done with at once
+fn loops(map: &mut Map) {
+    let mut seen: Vec<Vec<bool>> = map.tiles.iter().map(|row| vec![false; row.len()]).collect();
+    let mut found = Vec::new();
+    let mut open = 0;
+    for y in 0..map.y_dim {
+        for x in 0..map.tiles[y].len() {
+            let (towards, back) = match &map.tiles[y][x] {
+                Tile::Pipe(pipe) if !seen[y][x] => pipe.dir.clone(),
+                _ => continue,
+            };
+            let mut path = Vec::new();
+            let closed = walk(map, (x, y), towards, &mut path).is_ok();
+            if !closed {
+                // the rest of the chain lies the other way; walking it too
+                // keeps the chain from being counted again from its other tiles
+                let _ = walk(map, (x, y), back, &mut path);
+            }
+            for &(x, y) in &path {
+                seen[y][x] = true;
+            }
+            if closed {
+                found.push(path);
+            } else {
+                open += 1;
+            }
+        }
+    }
+    println!();
+    for (i, path) in found.iter().enumerate() {
+        map.mark(path);
+        let animal = if map.start.is_some_and(|start| path.contains(&start)) {
+            ", through S"
+        } else {
+            ""
         };
-        map.update(current.0, current.1, &tile);
-        let next_moves = next_move(&moves, current, x_max, y_max);
-        current = next_moves;
+        println!(
+            "loop {}: from {:?}, {} tiles, farthest {}, encloses {}{}",
+            i + 1,
+            path[0],
+            path.len(),
+            path.len() / 2,
+            shoelace(path),
+            animal
+        );
     }
+    println!("{} loops, {} open chains of pipes", found.len(), open);
+}
+
+// twice the signed area of the loop through every tile of `path`, then Pick's
+// theorem: A = i + b / 2 - 1, with the b tiles of the loop as boundary points
+fn shoelace(path: &[(usize, usize)]) -> usize {
+    let twice_area = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 as i64 * b.1 as i64 - b.0 as i64 * a.1 as i64)
+        .sum::<i64>()
+        .unsigned_abs() as usize;
+    (twice_area + 2 - path.len()) / 2
+}
+
+// walks every row from the left, flipping inside/outside on each vertical
+// crossing of the loop, and marks the tiles not on the loop accordingly
+fn scan(map: &mut Map) -> usize {
     let mut walker = Loc::Outside;
     let mut turn: Option<Kind> = None;
     let mut counter = 0;
@@ -404,12 +751,9 @@ fn part_two(mut map: Map, mut moves: Direction) {
                     }
                 }
             }
-            print!("{}", tile);
         }
         walker = Loc::Outside;
-        print!("\n");
     }
-    println!("\n{}", counter);
 
     // let mut current = map.start.clone();
     // let x_max = map.x_dim;
@@ -476,6 +820,7 @@ fn part_two(mut map: Map, mut moves: Direction) {
     //     print!("\n");
     // }
     // println!("{}", counter);
+    counter
 }
 
 // fn switch_loc(current: Loc, pipe: Pipe) -> Loc {
@@ -495,3 +840,26 @@ fn part_two(mut map: Map, mut moves: Direction) {
 //         current
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shoelace_agrees_with_scanner() {
+        let expected = [1, 1, 4, 4, 14, 8, 10];
+        for (i, enclosed) in expected.into_iter().enumerate() {
+            let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("src")
+                .join(format!("sample-{}.txt", i + 1));
+            let mut map = read_file(Line::new(file_path).unwrap()).unwrap();
+            let moves = infer_start(&mut map).unwrap();
+            let mut path = Vec::new();
+            walk(&map, map.start.unwrap(), moves, &mut path).unwrap();
+            map.mark(&path);
+            let picked = shoelace(&path);
+            assert_eq!(scan(&mut map), picked, "sample-{}", i + 1);
+            assert_eq!(picked, enclosed, "sample-{}", i + 1);
+        }
+    }
+}